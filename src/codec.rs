@@ -0,0 +1,313 @@
+//! A compact binary encoding of streams of [`struct@Spanned`] values, for caching token
+//! streams.
+//!
+//! A stream is encoded as its length followed by each item's span start, span end, and value.
+//! Integers, including the span offsets, are encoded as LEB128 varints, so small offsets take a
+//! single byte.
+
+use std::{error::Error, fmt::Display};
+
+use crate::Spanned;
+
+/// The reason a byte buffer could not be decoded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The buffer ended in the middle of a value.
+    UnexpectedEnd,
+    /// A varint was too large for the type being decoded.
+    Overflow,
+    /// A byte which must be `0` or `1` for a `bool` had another value.
+    InvalidBool(u8),
+    /// A code point which is not a valid `char`.
+    InvalidChar(u32),
+    /// A string was not valid UTF-8.
+    InvalidUtf8,
+    /// The buffer had `usize` bytes left over after the last value.
+    TrailingBytes(usize),
+}
+
+impl Display for DecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DecodeError::UnexpectedEnd => write!(f, "unexpected end of input"),
+            DecodeError::Overflow => write!(f, "varint overflowed its type"),
+            DecodeError::InvalidBool(byte) => write!(f, "invalid bool byte {}", byte),
+            DecodeError::InvalidChar(code) => write!(f, "invalid char code point {:#x}", code),
+            DecodeError::InvalidUtf8 => write!(f, "string is not valid UTF-8"),
+            DecodeError::TrailingBytes(len) => write!(f, "{} trailing bytes after input", len),
+        }
+    }
+}
+
+impl Error for DecodeError {}
+
+/// Types which can be written to the binary encoding.
+pub trait Encode {
+    /// Appends the encoding of `self` to `out`.
+    fn encode(&self, out: &mut Vec<u8>);
+}
+
+/// Types which can be read from the binary encoding.
+pub trait Decode: Sized {
+    /// Decodes a value from the front of `bytes`, advancing `bytes` past it.
+    fn decode(bytes: &mut &[u8]) -> Result<Self, DecodeError>;
+}
+
+/// Encodes `items` as a stream of spanned values.
+pub fn encode_stream<T: Encode>(items: &[Spanned<T>]) -> Vec<u8> {
+    let mut out = Vec::new();
+    items.len().encode(&mut out);
+    for item in items {
+        let span = item.span();
+        span.start.encode(&mut out);
+        span.end.encode(&mut out);
+        item.inner.encode(&mut out);
+    }
+    out
+}
+
+/// Decodes a stream of spanned values encoded by [`encode_stream`].
+///
+/// Returns an error rather than panicking if `bytes` is truncated or malformed, including if
+/// any bytes are left over after the stream.
+pub fn decode_stream<T: Decode>(mut bytes: &[u8]) -> Result<Vec<Spanned<T>>, DecodeError> {
+    let len = usize::decode(&mut bytes)?;
+    // Every item takes at least two bytes for its span, so don't trust a huge length.
+    let mut items = Vec::with_capacity(len.min(bytes.len() / 2));
+    for _ in 0..len {
+        let start = usize::decode(&mut bytes)?;
+        let end = usize::decode(&mut bytes)?;
+        items.push(Spanned::new(T::decode(&mut bytes)?, start..end));
+    }
+    if bytes.is_empty() {
+        Ok(items)
+    } else {
+        Err(DecodeError::TrailingBytes(bytes.len()))
+    }
+}
+
+fn encode_varint(mut value: u64, out: &mut Vec<u8>) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn decode_varint(bytes: &mut &[u8]) -> Result<u64, DecodeError> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let (&byte, rest) = bytes.split_first().ok_or(DecodeError::UnexpectedEnd)?;
+        *bytes = rest;
+        let bits = u64::from(byte & 0x7F);
+        if bits << shift >> shift != bits {
+            return Err(DecodeError::Overflow);
+        }
+        value |= bits << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(DecodeError::Overflow)
+}
+
+fn take<'a>(bytes: &mut &'a [u8], len: usize) -> Result<&'a [u8], DecodeError> {
+    if bytes.len() < len {
+        return Err(DecodeError::UnexpectedEnd);
+    }
+    let (taken, rest) = bytes.split_at(len);
+    *bytes = rest;
+    Ok(taken)
+}
+
+macro_rules! impl_varint {
+    ($($ty:ty),*) => {
+        $(
+            impl Encode for $ty {
+                fn encode(&self, out: &mut Vec<u8>) {
+                    encode_varint(*self as u64, out);
+                }
+            }
+
+            impl Decode for $ty {
+                fn decode(bytes: &mut &[u8]) -> Result<Self, DecodeError> {
+                    <$ty>::try_from(decode_varint(bytes)?).map_err(|_| DecodeError::Overflow)
+                }
+            }
+        )*
+    };
+}
+
+impl_varint!(u16, u32, u64, usize);
+
+impl Encode for u8 {
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.push(*self);
+    }
+}
+
+impl Decode for u8 {
+    fn decode(bytes: &mut &[u8]) -> Result<Self, DecodeError> {
+        Ok(take(bytes, 1)?[0])
+    }
+}
+
+impl Encode for bool {
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.push(u8::from(*self));
+    }
+}
+
+impl Decode for bool {
+    fn decode(bytes: &mut &[u8]) -> Result<Self, DecodeError> {
+        match u8::decode(bytes)? {
+            0 => Ok(false),
+            1 => Ok(true),
+            byte => Err(DecodeError::InvalidBool(byte)),
+        }
+    }
+}
+
+impl Encode for char {
+    fn encode(&self, out: &mut Vec<u8>) {
+        u32::from(*self).encode(out);
+    }
+}
+
+impl Decode for char {
+    fn decode(bytes: &mut &[u8]) -> Result<Self, DecodeError> {
+        let code = u32::decode(bytes)?;
+        char::from_u32(code).ok_or(DecodeError::InvalidChar(code))
+    }
+}
+
+impl Encode for str {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.len().encode(out);
+        out.extend_from_slice(self.as_bytes());
+    }
+}
+
+impl Encode for String {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.as_str().encode(out);
+    }
+}
+
+impl Decode for String {
+    fn decode(bytes: &mut &[u8]) -> Result<Self, DecodeError> {
+        let len = usize::decode(bytes)?;
+        let text = take(bytes, len)?;
+        String::from_utf8(text.to_vec()).map_err(|_| DecodeError::InvalidUtf8)
+    }
+}
+
+impl Encode for () {
+    fn encode(&self, _out: &mut Vec<u8>) {}
+}
+
+impl Decode for () {
+    fn decode(_bytes: &mut &[u8]) -> Result<Self, DecodeError> {
+        Ok(())
+    }
+}
+
+impl<T: Encode + ?Sized> Encode for &T {
+    fn encode(&self, out: &mut Vec<u8>) {
+        (**self).encode(out);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens() -> Vec<Spanned<String>> {
+        vec![
+            Spanned::new(String::from("let"), 0..3),
+            Spanned::new(String::from("名前"), 4..10),
+            Spanned::new(String::new(), 300..70_000),
+        ]
+    }
+
+    #[test]
+    fn round_trips_a_stream() {
+        let bytes = encode_stream(&tokens());
+        assert_eq!(decode_stream::<String>(&bytes), Ok(tokens()));
+
+        let flags = vec![Spanned::new(true, 1..2), Spanned::new(false, 5..5)];
+        assert_eq!(decode_stream(&encode_stream(&flags)), Ok(flags));
+        let chars = vec![Spanned::new('é', 0..2)];
+        assert_eq!(decode_stream(&encode_stream(&chars)), Ok(chars));
+        assert_eq!(
+            decode_stream::<()>(&encode_stream::<()>(&[])),
+            Ok(Vec::new())
+        );
+    }
+
+    #[test]
+    fn encodes_small_offsets_in_a_single_byte() {
+        let bytes = encode_stream(&[Spanned::new(7u8, 3..127)]);
+        assert_eq!(bytes, [1, 3, 127, 7]);
+        let bytes = encode_stream(&[Spanned::new((), 128..300)]);
+        assert_eq!(bytes, [1, 0x80, 0x01, 0xAC, 0x02]);
+    }
+
+    #[test]
+    fn truncated_buffers_fail_cleanly() {
+        let bytes = encode_stream(&tokens());
+        for len in 0..bytes.len() {
+            assert_eq!(
+                decode_stream::<String>(&bytes[..len]),
+                Err(DecodeError::UnexpectedEnd),
+                "at {}",
+                len
+            );
+        }
+    }
+
+    #[test]
+    fn trailing_bytes_are_rejected() {
+        let mut bytes = encode_stream(&tokens());
+        bytes.extend_from_slice(&[0, 0]);
+        assert_eq!(
+            decode_stream::<String>(&bytes),
+            Err(DecodeError::TrailingBytes(2))
+        );
+    }
+
+    #[test]
+    fn malformed_values_are_rejected() {
+        assert_eq!(
+            decode_stream::<bool>(&[1, 0, 1, 2]),
+            Err(DecodeError::InvalidBool(2))
+        );
+        assert_eq!(
+            decode_stream::<char>(&[1, 0, 1, 0x80, 0xB0, 0x03]),
+            Err(DecodeError::InvalidChar(0xD800))
+        );
+        assert_eq!(
+            decode_stream::<String>(&[1, 0, 1, 1, 0xFF]),
+            Err(DecodeError::InvalidUtf8)
+        );
+    }
+
+    #[test]
+    fn oversized_varints_are_rejected() {
+        assert_eq!(
+            decode_stream::<u16>(&[1, 0, 1, 0xFF, 0xFF, 0x04]),
+            Err(DecodeError::Overflow)
+        );
+        let mut bytes = vec![1, 0];
+        bytes.extend_from_slice(&[0xFF; 10]);
+        bytes.push(0x01);
+        assert_eq!(decode_stream::<()>(&bytes), Err(DecodeError::Overflow));
+    }
+
+    #[test]
+    fn a_huge_length_fails_without_allocating_for_it() {
+        let mut bytes = Vec::new();
+        u64::MAX.encode(&mut bytes);
+        assert_eq!(decode_stream::<()>(&bytes), Err(DecodeError::UnexpectedEnd));
+    }
+}
@@ -0,0 +1,115 @@
+use crate::{Sliced, Span, Spanned, SpannedVec};
+
+/// Structures which contain spans, such as an AST built from [`struct@Spanned`] values.
+///
+/// Implement this for your own types by calling `collect_spans` on each field.
+pub trait CollectSpans {
+    /// Pushes every span within `self` onto `out`, parents before their children.
+    fn collect_spans(&self, out: &mut Vec<Span>);
+}
+
+impl<T: CollectSpans> CollectSpans for Spanned<T> {
+    fn collect_spans(&self, out: &mut Vec<Span>) {
+        out.push(self.span());
+        self.inner.collect_spans(out);
+    }
+}
+
+impl<T: CollectSpans> CollectSpans for Sliced<'_, T> {
+    fn collect_spans(&self, out: &mut Vec<Span>) {
+        out.push(self.span());
+        self.inner.collect_spans(out);
+    }
+}
+
+impl<T: CollectSpans> CollectSpans for SpannedVec<T> {
+    fn collect_spans(&self, out: &mut Vec<Span>) {
+        out.push(self.span());
+        self.iter().for_each(|item| item.collect_spans(out));
+    }
+}
+
+impl<T: CollectSpans> CollectSpans for Vec<T> {
+    fn collect_spans(&self, out: &mut Vec<Span>) {
+        self.iter().for_each(|item| item.collect_spans(out));
+    }
+}
+
+impl<T: CollectSpans> CollectSpans for Option<T> {
+    fn collect_spans(&self, out: &mut Vec<Span>) {
+        if let Some(item) = self {
+            item.collect_spans(out);
+        }
+    }
+}
+
+impl<T: CollectSpans + ?Sized> CollectSpans for Box<T> {
+    fn collect_spans(&self, out: &mut Vec<Span>) {
+        T::collect_spans(self, out);
+    }
+}
+
+impl<T: CollectSpans + ?Sized> CollectSpans for &T {
+    fn collect_spans(&self, out: &mut Vec<Span>) {
+        T::collect_spans(self, out);
+    }
+}
+
+/// Implements `CollectSpans` for types which never contain spans.
+macro_rules! impl_collect_spans_leaf {
+    ($($ty:ty),*) => {
+        $(
+            impl CollectSpans for $ty {
+                fn collect_spans(&self, _out: &mut Vec<Span>) {}
+            }
+        )*
+    };
+}
+
+impl_collect_spans_leaf!(
+    (),
+    bool,
+    char,
+    u8,
+    u16,
+    u32,
+    u64,
+    u128,
+    usize,
+    i8,
+    i16,
+    i32,
+    i64,
+    i128,
+    isize,
+    f32,
+    f64,
+    str,
+    String
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn collects_spans_from_a_nested_list() {
+        let list = Spanned::new(vec![Spanned::new(1, 1..2), Spanned::new(2, 4..5)], 0..6);
+        let mut spans = Vec::new();
+        list.collect_spans(&mut spans);
+        assert_eq!(spans, [0..6, 1..2, 4..5]);
+    }
+
+    #[test]
+    fn collects_spans_through_options_and_slices() {
+        let source = "a b";
+        let items = vec![
+            Some(Sliced::new(Spanned::new('a', 0..1), 0..1, source)),
+            None,
+            Some(Sliced::new(Spanned::new('b', 2..3), 2..3, source)),
+        ];
+        let mut spans = Vec::new();
+        items.collect_spans(&mut spans);
+        assert_eq!(spans, [0..1, 0..1, 2..3, 2..3]);
+    }
+}
@@ -0,0 +1,58 @@
+//! A compact `serde` representation of [`struct@Spanned`] values as `[inner, start, end]`.
+//!
+//! Use it on a field with `#[serde(with = "slicey::compact")]`.
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::Spanned;
+
+/// Serializes `spanned` as the sequence `[inner, start, end]`.
+pub fn serialize<T, S>(spanned: &Spanned<T>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Serialize,
+    S: Serializer,
+{
+    let span = spanned.span();
+    (&spanned.inner, span.start, span.end).serialize(serializer)
+}
+
+/// Deserializes a `Spanned` value from the sequence `[inner, start, end]`.
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Spanned<T>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    let (inner, start, end) = <(T, usize, usize)>::deserialize(deserializer)?;
+    Ok(Spanned::new(inner, start..end))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Token {
+        #[serde(with = "crate::compact")]
+        name: Spanned<String>,
+    }
+
+    #[test]
+    fn round_trips_through_the_compact_json_shape() {
+        let token = Token {
+            name: Spanned::new(String::from("main"), 3..7),
+        };
+        let json = serde_json::to_string(&token).unwrap();
+        assert_eq!(json, r#"{"name":["main",3,7]}"#);
+        assert_eq!(serde_json::from_str::<Token>(&json).unwrap(), token);
+    }
+
+    #[test]
+    fn is_smaller_than_the_default_struct_form() {
+        let spanned = Spanned::new(1, 10..20);
+        let default = serde_json::to_string(&spanned).unwrap();
+        assert_eq!(default, r#"{"inner":1,"span":{"start":10,"end":20}}"#);
+        let mut compact = Vec::new();
+        serialize(&spanned, &mut serde_json::Serializer::new(&mut compact)).unwrap();
+        assert_eq!(compact, br#"[1,10,20]"#);
+    }
+}
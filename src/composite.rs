@@ -0,0 +1,120 @@
+use crate::{span::contains, Sliced, Span, Spanned};
+
+/// A single logical source made of several source strings laid end to end, such as a file and
+/// the files it includes.
+///
+/// Spans into the composite source can be resolved back to the source they came from.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CompositeSource<'a> {
+    segments: Vec<(&'a str, Span)>,
+}
+
+impl<'a> CompositeSource<'a> {
+    /// Creates a new, empty `CompositeSource`.
+    pub fn new() -> Self {
+        Self {
+            segments: Vec::new(),
+        }
+    }
+
+    /// Appends `source` to the end of the composite source, returning its index and the range
+    /// it occupies in the composite source.
+    pub fn push(&mut self, source: &'a str) -> (usize, Span) {
+        let start = self.len();
+        let range = start..start + source.len();
+        self.segments.push((source, range.clone()));
+        (self.segments.len() - 1, range)
+    }
+
+    /// The total length of the composite source in bytes.
+    pub fn len(&self) -> usize {
+        self.segments.last().map_or(0, |(_, range)| range.end)
+    }
+
+    /// Returns `true` if the composite source has no sources or only empty sources.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The source string with index `index` and the range it occupies in the composite source.
+    pub fn get(&self, index: usize) -> Option<(&'a str, Span)> {
+        self.segments.get(index).cloned()
+    }
+
+    /// Resolves a span in the composite source to the index of the source it lies in and the
+    /// equivalent span local to that source.
+    ///
+    /// Returns `None` if the span is inverted or doesn't lie entirely within a single source. An
+    /// empty span on the boundary of two sources resolves to the first of them.
+    pub fn resolve(&self, span: Span) -> Option<(usize, Span)> {
+        if span.start > span.end {
+            return None;
+        }
+        let index = self
+            .segments
+            .partition_point(|(_, range)| range.end < span.end);
+        let (_, range) = self.segments.get(index)?;
+        if !contains(range, &span) {
+            return None;
+        }
+        Some((index, span.start - range.start..span.end - range.start))
+    }
+
+    /// Resolves a value spanned in the composite source to a `Sliced` value over the source it
+    /// lies in, along with that source's index. See [`CompositeSource::resolve`].
+    pub fn resolve_spanned<T>(&self, spanned: Spanned<T>) -> Option<(usize, Sliced<'a, T>)> {
+        let (index, span) = self.resolve(spanned.span())?;
+        let (source, _) = self.segments[index];
+        Some((index, Sliced::new(spanned.unwrap(), span, source)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn composite() -> CompositeSource<'static> {
+        let mut composite = CompositeSource::new();
+        assert_eq!(composite.push("main"), (0, 0..4));
+        assert_eq!(composite.push("lib.rs"), (1, 4..10));
+        assert_eq!(composite.push("end"), (2, 10..13));
+        composite
+    }
+
+    #[test]
+    fn resolves_spans_in_each_segment() {
+        let composite = composite();
+        assert_eq!(composite.len(), 13);
+        assert_eq!(composite.resolve(1..3), Some((0, 1..3)));
+        assert_eq!(composite.resolve(4..7), Some((1, 0..3)));
+        assert_eq!(composite.resolve(11..13), Some((2, 1..3)));
+    }
+
+    #[test]
+    fn rejects_spans_crossing_segments_or_past_the_end() {
+        let composite = composite();
+        assert_eq!(composite.resolve(3..5), None);
+        assert_eq!(composite.resolve(12..14), None);
+        assert_eq!(composite.resolve(4..4), Some((0, 4..4)));
+    }
+
+    #[test]
+    fn resolve_spanned_slices_the_originating_source() {
+        let (index, sliced) = composite()
+            .resolve_spanned(Spanned::new("lib", 4..7))
+            .unwrap();
+        assert_eq!(index, 1);
+        assert_eq!((sliced.slice(), sliced.source()), ("lib", "lib.rs"));
+    }
+
+    #[test]
+    #[allow(clippy::reversed_empty_ranges)]
+    fn rejects_inverted_spans() {
+        assert_eq!(composite().resolve(9..5), None);
+
+        let mut single = CompositeSource::new();
+        single.push("0123456789");
+        assert_eq!(single.resolve(9..3), None);
+        assert!(single.resolve_spanned(Spanned::new((), 9..3)).is_none());
+    }
+}
@@ -0,0 +1,103 @@
+use crate::{span::overlaps, Span, Spanned};
+
+/// How the spans of a list of items cover a source, created by [`coverage`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Coverage {
+    /// The regions of the source covered by no item, in order.
+    pub gaps: Vec<Span>,
+    /// Every pair of overlapping spans, earlier span first, in order of the later span.
+    pub overlaps: Vec<(Span, Span)>,
+}
+
+impl Coverage {
+    /// Returns `true` if every byte of the source is covered by some item.
+    pub fn is_total(&self) -> bool {
+        self.gaps.is_empty()
+    }
+
+    /// Returns `true` if no two items overlap.
+    pub fn is_disjoint(&self) -> bool {
+        self.overlaps.is_empty()
+    }
+
+    /// Returns `true` if every byte of the source is covered by exactly one item, as for the
+    /// output of a lossless tokenizer.
+    pub fn is_exact(&self) -> bool {
+        self.is_total() && self.is_disjoint()
+    }
+}
+
+/// Checks how the spans of `items` cover a source of `source_len` bytes, finding the gaps no
+/// item covers and the pairs of items which overlap.
+///
+/// `items` needn't be sorted. Empty spans never overlap anything, and parts of spans past
+/// `source_len` are ignored when finding gaps.
+pub fn coverage<T>(items: &[Spanned<T>], source_len: usize) -> Coverage {
+    let mut spans: Vec<Span> = items.iter().map(Spanned::span).collect();
+    spans.sort_by_key(|span| (span.start, span.end));
+
+    let mut coverage = Coverage::default();
+    let mut covered = 0;
+    let mut active: Vec<Span> = Vec::new();
+    for span in spans {
+        if span.start > covered && covered < source_len {
+            coverage.gaps.push(covered..span.start.min(source_len));
+        }
+        covered = covered.max(span.end);
+
+        active.retain(|earlier| earlier.end > span.start);
+        for earlier in &active {
+            if overlaps(earlier, &span) {
+                coverage.overlaps.push((earlier.clone(), span.clone()));
+            }
+        }
+        active.push(span);
+    }
+    if covered < source_len {
+        coverage.gaps.push(covered..source_len);
+    }
+    coverage
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn items(spans: &[Span]) -> Vec<Spanned<()>> {
+        spans
+            .iter()
+            .map(|span| Spanned::new((), span.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn a_fully_covering_stream_is_exact() {
+        let coverage = coverage(&items(&[4..7, 0..3, 3..4, 7..7, 7..10]), 10);
+        assert_eq!(coverage, Coverage::default());
+        assert!(coverage.is_exact());
+    }
+
+    #[test]
+    #[allow(clippy::single_range_in_vec_init)]
+    fn a_gapped_stream_reports_every_gap() {
+        let coverage = coverage(&items(&[2..4, 5..8]), 10);
+        assert_eq!(coverage.gaps, [0..2, 4..5, 8..10]);
+        assert!(!coverage.is_total());
+        assert!(coverage.is_disjoint());
+
+        assert_eq!(super::coverage(&items(&[]), 3).gaps, [0..3]);
+        assert!(super::coverage(&items(&[0..5]), 3).is_exact());
+    }
+
+    #[test]
+    fn an_overlapping_stream_reports_every_pair() {
+        let coverage = coverage(&items(&[0..6, 2..4, 3..8, 8..9]), 9);
+        assert_eq!(
+            coverage.overlaps,
+            [(0..6, 2..4), (0..6, 3..8), (2..4, 3..8)]
+        );
+        assert!(coverage.is_total());
+        assert!(!coverage.is_disjoint());
+        assert!(!coverage.is_exact());
+    }
+}
@@ -0,0 +1,91 @@
+use crate::{span_union, Span, Spanned};
+
+/// A peekable cursor over a slice of [`struct@Spanned`] tokens which tracks the span of the
+/// tokens consumed so far.
+#[derive(Debug)]
+pub struct TokenCursor<'a, T> {
+    tokens: &'a [Spanned<T>],
+    position: usize,
+    consumed: Option<Span>,
+}
+
+impl<'a, T> TokenCursor<'a, T> {
+    /// Creates a new `TokenCursor` positioned at the first of `tokens`.
+    pub fn new(tokens: &'a [Spanned<T>]) -> Self {
+        Self {
+            tokens,
+            position: 0,
+            consumed: None,
+        }
+    }
+
+    /// The next token, without consuming it.
+    pub fn peek(&self) -> Option<&'a Spanned<T>> {
+        self.tokens.get(self.position)
+    }
+
+    /// The index of the next token, that is, the number of tokens consumed so far.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// The span covering every token consumed so far, or `0..0` if none have been consumed.
+    pub fn span_so_far(&self) -> Span {
+        self.consumed.clone().unwrap_or(0..0)
+    }
+}
+
+impl<'a, T> Iterator for TokenCursor<'a, T> {
+    type Item = &'a Spanned<T>;
+
+    /// Consumes and returns the next token.
+    fn next(&mut self) -> Option<Self::Item> {
+        let token = self.tokens.get(self.position)?;
+        self.position += 1;
+        self.consumed = Some(match self.consumed.take() {
+            Some(consumed) => span_union(&[consumed, token.span()]),
+            None => token.span(),
+        });
+        Some(token)
+    }
+}
+
+impl<T> Clone for TokenCursor<'_, T> {
+    fn clone(&self) -> Self {
+        Self {
+            tokens: self.tokens,
+            position: self.position,
+            consumed: self.consumed.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn span_so_far_grows_as_tokens_are_consumed() {
+        let tokens = [
+            Spanned::new('a', 0..1),
+            Spanned::new('b', 2..4),
+            Spanned::new('c', 5..6),
+        ];
+        let mut cursor = TokenCursor::new(&tokens);
+        assert_eq!(cursor.span_so_far(), 0..0);
+        assert_eq!(cursor.peek(), Some(&tokens[0]));
+        assert_eq!(cursor.position(), 0);
+
+        assert_eq!(cursor.next(), Some(&tokens[0]));
+        assert_eq!(cursor.span_so_far(), 0..1);
+        assert_eq!(cursor.next(), Some(&tokens[1]));
+        assert_eq!(cursor.span_so_far(), 0..4);
+        assert_eq!(cursor.position(), 2);
+        assert_eq!(cursor.next(), Some(&tokens[2]));
+        assert_eq!(cursor.span_so_far(), 0..6);
+
+        assert_eq!(cursor.peek(), None);
+        assert_eq!(cursor.next(), None);
+        assert_eq!(cursor.position(), 3);
+    }
+}
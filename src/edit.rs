@@ -0,0 +1,196 @@
+use crate::{span, Span, SpanError, SpanMap};
+
+/// Replaces the text of `source` covered by `span` with `replacement`.
+///
+/// # Panics
+/// Panics if `span` is not valid for `source`, see [`SpanError`].
+pub(crate) fn replace(source: &str, span: &Span, replacement: &str) -> String {
+    let mut edited =
+        String::with_capacity(source.len().saturating_sub(span.len()) + replacement.len());
+    edited.push_str(&source[..span.start]);
+    edited.push_str(replacement);
+    edited.push_str(&source[span.end..]);
+    edited
+}
+
+/// Applies every edit to `source` in one pass, replacing the text covered by each span with the
+/// corresponding replacement.
+///
+/// Spans are in the coordinates of the original `source` and edits may be given in any order.
+/// Edits may touch but must not overlap, and any number of insertions (empty spans) may be made
+/// at the same position, in which case they're applied in the order given.
+///
+/// Returns an error if any span is not valid for `source` or if two spans overlap.
+pub fn apply_edits(source: &str, edits: &[(Span, &str)]) -> Result<String, SpanError> {
+    let mut order: Vec<usize> = (0..edits.len()).collect();
+    order.sort_by_key(|&i| (edits[i].0.start, edits[i].0.end));
+
+    let mut edited = String::with_capacity(source.len());
+    let mut end = 0;
+    let mut previous: Option<&Span> = None;
+    for i in order {
+        let (span, replacement) = &edits[i];
+        span::validate(span, source)?;
+        if let Some(previous) = previous.filter(|previous| previous.end > span.start) {
+            return Err(SpanError::Overlapping(previous.clone(), span.clone()));
+        }
+        edited.push_str(&source[end..span.start]);
+        edited.push_str(replacement);
+        end = span.end;
+        previous = Some(span);
+    }
+    edited.push_str(&source[end..]);
+    Ok(edited)
+}
+
+/// Applies every edit to `source` like [`apply_edits`], also returning a [`SpanMap`] from the
+/// coordinates of `source` to those of the edited source.
+///
+/// The map can be used to move spans into the edited source with [`SpanMap::to_generated`], or
+/// back with [`SpanMap::to_original`].
+///
+/// Returns an error if any span is not valid for `source` or if two spans overlap.
+pub fn rewrite(source: &str, edits: &[(Span, String)]) -> Result<(String, SpanMap), SpanError> {
+    let mut edits: Vec<(Span, &str)> = edits
+        .iter()
+        .map(|(span, replacement)| (span.clone(), replacement.as_str()))
+        .collect();
+    let rewritten = apply_edits(source, &edits)?;
+    edits.sort_by_key(|(span, _)| (span.start, span.end));
+    let mut map = SpanMap::new();
+    for (span, replacement) in edits {
+        map.push(span, replacement.len())?;
+    }
+    Ok((rewritten, map))
+}
+
+/// Finds the regions of `new` which differ from `old`, in the coordinates of `new`.
+///
+/// This only trims the longest common prefix and suffix, so there is at most one region. A
+/// region that was only deleted from `old` is returned as an empty span at the position of the
+/// deletion. Returns no regions if the strings are equal. Regions always lie on `char`
+/// boundaries.
+pub fn diff_spans(old: &str, new: &str) -> Vec<Span> {
+    if old == new {
+        return Vec::new();
+    }
+    let mut prefix = old
+        .bytes()
+        .zip(new.bytes())
+        .take_while(|(a, b)| a == b)
+        .count();
+    while !new.is_char_boundary(prefix) {
+        prefix -= 1;
+    }
+    let max_suffix = old.len().min(new.len()) - prefix;
+    let mut suffix = old
+        .bytes()
+        .rev()
+        .zip(new.bytes().rev())
+        .take(max_suffix)
+        .take_while(|(a, b)| a == b)
+        .count();
+    while !new.is_char_boundary(new.len() - suffix) {
+        suffix -= 1;
+    }
+    let changed = prefix..new.len() - suffix;
+    vec![changed]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Spanned;
+
+    #[test]
+    fn replace_in_swaps_the_text_of_a_span() {
+        let source = "let x = 1;";
+        assert_eq!(
+            Spanned::new((), 4..5).replace_in(source, "value"),
+            "let value = 1;"
+        );
+    }
+
+    #[test]
+    fn apply_edits_applies_a_batch_in_any_order() {
+        let source = "let x = 1;";
+        let edits = [(8..9, "2"), (0..3, "const"), (4..5, "Y"), (10..10, " // y")];
+        assert_eq!(
+            apply_edits(source, &edits),
+            Ok(String::from("const Y = 2; // y"))
+        );
+    }
+
+    #[test]
+    fn apply_edits_rejects_overlapping_edits() {
+        let edits = [(0..5, "a"), (3..7, "b")];
+        assert_eq!(
+            apply_edits("let x = 1;", &edits),
+            Err(SpanError::Overlapping(0..5, 3..7))
+        );
+    }
+
+    #[test]
+    fn apply_edits_allows_touching_edits_and_repeated_insertions() {
+        let edits = [(0..1, "A"), (1..2, "B"), (2..2, "x"), (2..2, "y")];
+        assert_eq!(apply_edits("abc", &edits), Ok(String::from("ABxyc")));
+    }
+
+    #[test]
+    fn apply_edits_rejects_an_invalid_span() {
+        let edits = [(2..9, "")];
+        assert_eq!(
+            apply_edits("abc", &edits),
+            Err(SpanError::OutOfBounds { span: 2..9, len: 3 })
+        );
+    }
+
+    #[test]
+    #[allow(clippy::single_range_in_vec_init)]
+    fn diff_spans_finds_a_single_character_edit() {
+        assert_eq!(diff_spans("let x = 1;", "let y = 1;"), [4..5]);
+        assert_eq!(diff_spans("let é = 1;", "let ü = 1;"), [4..6]);
+    }
+
+    #[test]
+    #[allow(clippy::single_range_in_vec_init)]
+    fn diff_spans_finds_an_append_and_a_deletion() {
+        assert_eq!(diff_spans("let x", "let x = 1;"), [5..10]);
+        assert_eq!(diff_spans("let x = 1;", "let x"), [5..5]);
+    }
+
+    #[test]
+    #[allow(clippy::single_range_in_vec_init)]
+    fn diff_spans_of_equal_and_entirely_different_strings() {
+        assert!(diff_spans("same", "same").is_empty());
+        assert_eq!(diff_spans("abc", "xyz"), [0..3]);
+        assert_eq!(diff_spans("", "new"), [0..3]);
+    }
+
+    #[test]
+    #[allow(clippy::single_range_in_vec_init)]
+    fn diff_spans_does_not_double_count_a_repeated_run() {
+        assert_eq!(diff_spans("aa", "aaa"), [2..3]);
+    }
+
+    #[test]
+    fn rewrite_maps_spans_after_edits_of_differing_length() {
+        let source = "let x = 1; let y = 2;";
+        let edits = [(19..20, String::from("20")), (4..5, String::from("value"))];
+        let (rewritten, map) = rewrite(source, &edits).unwrap();
+        assert_eq!(rewritten, "let value = 1; let y = 20;");
+        assert_eq!(map.to_generated(&(20..21)), Some(25..26));
+        assert_eq!(&rewritten[25..26], ";");
+        assert_eq!(map.to_generated(&(15..18)), Some(19..22));
+        assert_eq!(map.to_original(&(6..9)), None);
+    }
+
+    #[test]
+    fn rewrite_rejects_overlapping_edits() {
+        let edits = [(0..3, String::from("a")), (2..4, String::from("b"))];
+        assert_eq!(
+            rewrite("abcdef", &edits),
+            Err(SpanError::Overlapping(0..3, 2..4))
+        );
+    }
+}
@@ -0,0 +1,46 @@
+use std::{error::Error, fmt::Display};
+
+use crate::Span;
+
+/// The reason a span is not valid for a source string, or for use alongside other spans.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SpanError {
+    /// The span's start is greater than its end.
+    Inverted(Span),
+    /// The span ends past the end of the source, which is `len` bytes long.
+    OutOfBounds { span: Span, len: usize },
+    /// The byte `index`, an end of the span, does not lie on a `char` boundary.
+    NotCharBoundary { span: Span, index: usize },
+    /// Two spans which must be disjoint overlap.
+    Overlapping(Span, Span),
+    /// Moving or resizing the span would take an end of it past `usize::MAX`.
+    Overflow(Span),
+}
+
+impl Display for SpanError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SpanError::Inverted(span) => write!(f, "span {:?} is inverted", span),
+            SpanError::OutOfBounds { span, len } => {
+                write!(
+                    f,
+                    "span {:?} is out of bounds of a source of length {}",
+                    span, len
+                )
+            }
+            SpanError::NotCharBoundary { span, index } => {
+                write!(
+                    f,
+                    "span {:?} does not lie on a char boundary at {}",
+                    span, index
+                )
+            }
+            SpanError::Overlapping(first, second) => {
+                write!(f, "spans {:?} and {:?} overlap", first, second)
+            }
+            SpanError::Overflow(span) => write!(f, "span {:?} overflowed", span),
+        }
+    }
+}
+
+impl Error for SpanError {}
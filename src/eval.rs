@@ -0,0 +1,69 @@
+use std::{
+    any::Any,
+    panic::{catch_unwind, AssertUnwindSafe},
+};
+
+use crate::Spanned;
+
+/// Runs `f` on the wrapped value, turning a panic into an error at the location of `spanned`.
+///
+/// On panic, the panic message is returned associated with the span of `spanned`, so that
+/// a failure such as an integer overflow while evaluating an expression can be reported where
+/// the expression is. Panics with a payload other than a string get a generic message.
+///
+/// `f` is treated as unwind safe, so any state it shares with the caller may be left
+/// inconsistent by a panic. The panic hook still runs as normal, so by default the panic is
+/// also printed to standard error. Panics are only caught if the panic strategy is `unwind`.
+pub fn eval_at<T, R>(spanned: &Spanned<T>, f: impl FnOnce(&T) -> R) -> Result<R, Spanned<String>> {
+    catch_unwind(AssertUnwindSafe(|| f(&spanned.inner)))
+        .map_err(|payload| Spanned::new(panic_message(payload), spanned.span()))
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => match payload.downcast::<&'static str>() {
+            Ok(message) => message.to_string(),
+            Err(_) => "panicked with a non-string payload".to_string(),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn returns_the_result_when_f_does_not_panic() {
+        let expr = Spanned::new(2, 4..9);
+        assert_eq!(eval_at(&expr, |x| x * 21), Ok(42));
+    }
+
+    #[test]
+    fn a_panic_is_reported_at_the_span() {
+        let expr = Spanned::new(1, 4..9);
+        let error = eval_at(&expr, |x| {
+            i32::MAX.checked_add(*x).expect("integer overflow")
+        });
+        assert_eq!(
+            error,
+            Err(Spanned::new(String::from("integer overflow"), 4..9))
+        );
+
+        let error = eval_at(&expr, |x| -> i32 { panic!("cannot divide {} by zero", x) });
+        assert_eq!(
+            error,
+            Err(Spanned::new(String::from("cannot divide 1 by zero"), 4..9))
+        );
+    }
+
+    #[test]
+    fn a_non_string_payload_gets_a_generic_message() {
+        let expr = Spanned::new((), 0..1);
+        let error = eval_at(&expr, |_| std::panic::panic_any(7));
+        assert_eq!(
+            error.map_err(Spanned::unwrap),
+            Err::<(), _>(String::from("panicked with a non-string payload"))
+        );
+    }
+}
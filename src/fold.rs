@@ -0,0 +1,84 @@
+use crate::Spanned;
+
+/// Structures containing [`struct@Spanned`] values which can be transformed bottom-up.
+///
+/// Implement this for `Spanned<Node>` of a recursive AST `Node` by folding the children of a
+/// node before passing the node itself to `f`.
+pub trait SpannedFold<T>: Sized {
+    /// Applies `f` to every `Spanned<T>` within `self`, children before their parents.
+    fn fold(self, f: &mut impl FnMut(Spanned<T>) -> Spanned<T>) -> Self;
+}
+
+impl<T> SpannedFold<T> for Spanned<Vec<Spanned<T>>> {
+    /// Applies `f` to each element in order, keeping the span of the list itself.
+    fn fold(self, f: &mut impl FnMut(Spanned<T>) -> Spanned<T>) -> Self {
+        let span = self.span();
+        Spanned::new(self.unwrap().into_iter().map(&mut *f).collect(), span)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Span;
+
+    #[test]
+    fn fold_rewrites_leaves_and_keeps_spans() {
+        let list = Spanned::new(vec![Spanned::new(1, 1..2), Spanned::new(2, 4..5)], 0..6);
+        let folded = list.fold(&mut |leaf| leaf.map_ref(|n| n * 10));
+        assert_eq!(folded.span(), 0..6);
+        assert_eq!(
+            folded.inner,
+            [Spanned::new(10, 1..2), Spanned::new(20, 4..5)]
+        );
+    }
+
+    #[derive(Debug, PartialEq)]
+    enum Node {
+        Leaf(i32),
+        List(Vec<Spanned<Node>>),
+    }
+
+    impl SpannedFold<Node> for Spanned<Node> {
+        fn fold(self, f: &mut impl FnMut(Spanned<Node>) -> Spanned<Node>) -> Self {
+            let span = self.span();
+            let node = match self.unwrap() {
+                Node::List(children) => {
+                    Node::List(children.into_iter().map(|child| child.fold(f)).collect())
+                }
+                leaf => leaf,
+            };
+            f(Spanned::new(node, span))
+        }
+    }
+
+    #[test]
+    fn fold_recurses_bottom_up_through_a_tree() {
+        let tree = Spanned::new(
+            Node::List(vec![
+                Spanned::new(Node::Leaf(1), 1..2),
+                Spanned::new(Node::List(vec![Spanned::new(Node::Leaf(2), 4..5)]), 3..6),
+            ]),
+            0..7,
+        );
+        let mut visited: Vec<Span> = Vec::new();
+        let folded = tree.fold(&mut |node| {
+            visited.push(node.span());
+            node.map_with_span(|node, _| match node {
+                Node::Leaf(n) => Node::Leaf(-n),
+                list => list,
+            })
+        });
+        assert_eq!(visited, [1..2, 4..5, 3..6, 0..7]);
+        assert_eq!(
+            folded,
+            Spanned::new(
+                Node::List(vec![
+                    Spanned::new(Node::Leaf(-1), 1..2),
+                    Spanned::new(Node::List(vec![Spanned::new(Node::Leaf(-2), 4..5)]), 3..6),
+                ]),
+                0..7,
+            )
+        );
+    }
+}
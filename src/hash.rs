@@ -0,0 +1,90 @@
+use std::hash::Hasher;
+
+/// A 64-bit FNV-1a hasher, which unlike `std`'s default hasher uses no random state, so hashes
+/// are identical from run to run.
+///
+/// Integers are hashed as fixed-width little-endian bytes, with `usize` and `isize` widened to
+/// 64 bits, so hashes are also identical across platforms of different widths and endianness.
+pub(crate) struct FnvHasher(u64);
+
+impl FnvHasher {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    pub(crate) fn new() -> Self {
+        Self(Self::OFFSET_BASIS)
+    }
+}
+
+/// Implements `Hasher` methods which hash an integer as its little-endian bytes.
+macro_rules! write_le {
+    ($($method:ident: $ty:ty),* $(,)?) => {
+        $(
+            fn $method(&mut self, i: $ty) {
+                self.write(&i.to_le_bytes());
+            }
+        )*
+    };
+}
+
+impl Hasher for FnvHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 ^ u64::from(byte)).wrapping_mul(Self::PRIME);
+        }
+    }
+
+    write_le!(
+        write_u8: u8,
+        write_u16: u16,
+        write_u32: u32,
+        write_u64: u64,
+        write_u128: u128,
+        write_i8: i8,
+        write_i16: i16,
+        write_i32: i32,
+        write_i64: i64,
+        write_i128: i128,
+    );
+
+    fn write_usize(&mut self, i: usize) {
+        self.write_u64(i as u64);
+    }
+
+    fn write_isize(&mut self, i: isize) {
+        self.write_i64(i as i64);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_the_fnv1a_test_vectors() {
+        let hash = |bytes: &[u8]| {
+            let mut hasher = FnvHasher::new();
+            hasher.write(bytes);
+            hasher.finish()
+        };
+        assert_eq!(hash(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(hash(b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(hash(b"foobar"), 0x8594_4171_f739_67e8);
+    }
+
+    #[test]
+    fn hashes_integers_as_fixed_width_little_endian_bytes() {
+        let mut bytes = FnvHasher::new();
+        bytes.write(&[1, 0, 0, 0, 0, 0, 0, 0]);
+        let mut usize = FnvHasher::new();
+        usize.write_usize(1);
+        let mut u64 = FnvHasher::new();
+        u64.write_u64(1);
+        assert_eq!(usize.finish(), bytes.finish());
+        assert_eq!(u64.finish(), bytes.finish());
+    }
+}
@@ -0,0 +1,52 @@
+use crate::{Span, Spanned};
+
+/// Positions from a parser which can be attached to values as spans.
+///
+/// Implement this once for your parser's position type to write `pos.spanned(value)` wherever
+/// a value is produced.
+pub trait IntoSpanned {
+    /// Wraps `value` in a [`struct@Spanned`] associated with the span of `self`.
+    fn spanned<T>(self, value: T) -> Spanned<T>;
+}
+
+impl IntoSpanned for Span {
+    fn spanned<T>(self, value: T) -> Spanned<T> {
+        Spanned::new(value, self)
+    }
+}
+
+/// A `(start, end)` pair of byte offsets.
+impl IntoSpanned for (usize, usize) {
+    fn spanned<T>(self, value: T) -> Spanned<T> {
+        Spanned::new(value, self.0..self.1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A position as a parser might track it, with an offset and a length.
+    struct Pos {
+        offset: usize,
+        len: usize,
+    }
+
+    impl IntoSpanned for Pos {
+        fn spanned<T>(self, value: T) -> Spanned<T> {
+            (self.offset..self.offset + self.len).spanned(value)
+        }
+    }
+
+    #[test]
+    fn wraps_values_from_ranges_and_pairs() {
+        assert_eq!((2..5).spanned('a'), Spanned::new('a', 2..5));
+        assert_eq!((2, 5).spanned('a'), Spanned::new('a', 2..5));
+    }
+
+    #[test]
+    fn can_be_implemented_for_a_custom_position() {
+        let token = Pos { offset: 4, len: 3 }.spanned("let");
+        assert_eq!(token, Spanned::new("let", 4..7));
+    }
+}
@@ -8,12 +8,76 @@
 
 use std::ops::Range;
 
+pub use collect_spans::CollectSpans;
+pub use composite::CompositeSource;
+pub use coverage::{coverage, Coverage};
+pub use cursor::TokenCursor;
+pub use edit::{apply_edits, diff_spans, rewrite};
+pub use error::SpanError;
+pub use eval::eval_at;
+pub use fold::SpannedFold;
+pub use into_spanned::IntoSpanned;
+pub use line_index::LineIndex;
+pub use registry::SpanRegistry;
+pub use resolved::ResolvedSliced;
+pub use seq::{
+    absolutize, adjacent_pairs, coalesce, collect_results, first_at, gaps, group_by_line,
+    index_by_start, keep_widest, lift, longest, merge_sorted, overlapping, partition_by_span,
+    range_query, shortest, sort_by_span, sort_by_span_desc, validate_all, within, zip3, zip_all,
+    zip_map, zip_optional_spanned, zip_spanned,
+};
 pub use sliced::Sliced;
+pub use sliced_bytes::SlicedBytes;
 pub use slicey_derive::{Sliced, Spanned};
+pub use snippet::Snippet;
+pub use span::{from_line_col_range, matched_region, span_union, HasSpan};
+pub use span_map::{remap_through, SpanMap};
 pub use spanned::Spanned;
+pub use spanned_vec::SpannedVec;
+#[cfg(feature = "text-size")]
+pub use text_size::TextRangeError;
+pub use trivia::{with_trivia, Piece};
+#[cfg(feature = "unicode-width")]
+pub use unicode_width::align_annotations;
 
+#[cfg(feature = "codec")]
+pub mod codec;
+mod collect_spans;
+#[cfg(feature = "serde")]
+pub mod compact;
+mod composite;
+mod coverage;
+mod cursor;
+mod edit;
+mod error;
+mod eval;
+mod fold;
+mod hash;
+mod into_spanned;
+mod line_index;
+#[cfg(feature = "lsp")]
+mod lsp;
+mod macros;
+#[cfg(feature = "pest")]
+mod pest;
+mod pretty;
+#[cfg(feature = "regex")]
+mod regex;
+mod registry;
+mod resolved;
+mod seq;
 mod sliced;
+mod sliced_bytes;
+mod snippet;
+mod span;
+mod span_map;
 mod spanned;
+mod spanned_vec;
+#[cfg(feature = "text-size")]
+mod text_size;
+mod trivia;
+#[cfg(feature = "unicode-width")]
+mod unicode_width;
 
 /// Represents a range in a source text.
 /// `Span` is a shorthand for a range of indices in the source, defined as `Range<usize>`.
@@ -0,0 +1,191 @@
+use std::fmt::Display;
+
+use crate::{span::char_count, Sliced, Spanned};
+
+/// A table of the line starts in a source string, for resolving many byte offsets to lines and
+/// columns without rescanning the source each time.
+///
+/// With the `memchr` feature enabled, newlines are found with the `memchr` crate when building the
+/// table.
+#[derive(Debug, Clone)]
+pub struct LineIndex<'source> {
+    source: &'source str,
+    line_starts: Vec<usize>,
+}
+
+impl<'source> LineIndex<'source> {
+    /// Builds the line table for `source`.
+    pub fn new(source: &'source str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(newlines(source.as_bytes()).map(|i| i + 1))
+            .collect();
+        Self {
+            source,
+            line_starts,
+        }
+    }
+
+    /// The source string the table was built from.
+    pub fn source(&self) -> &'source str {
+        self.source
+    }
+
+    /// The number of lines in the source, counting the (possibly empty) line after a trailing
+    /// newline.
+    pub fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    /// Computes the 1-based line and column of the byte `offset` in the source.
+    ///
+    /// Columns are counted in `char`s, and offsets past the end of the source are clamped to its
+    /// length, exactly as for [`Sliced::line_col`].
+    pub fn line_col(&self, offset: usize) -> (usize, usize) {
+        let offset = offset.min(self.source.len());
+        let line = self.line_starts.partition_point(|&start| start <= offset);
+        let line_start = self.line_starts[line - 1];
+        let column = char_count(&self.source.as_bytes()[line_start..offset]) + 1;
+        (line, column)
+    }
+}
+
+#[cfg(feature = "memchr")]
+fn newlines(bytes: &[u8]) -> impl Iterator<Item = usize> + '_ {
+    memchr::memchr_iter(b'\n', bytes)
+}
+
+#[cfg(not(feature = "memchr"))]
+fn newlines(bytes: &[u8]) -> impl Iterator<Item = usize> + '_ {
+    bytes
+        .iter()
+        .enumerate()
+        .filter(|&(_, &b)| b == b'\n')
+        .map(|(i, _)| i)
+}
+
+impl<T> Spanned<T> {
+    /// The 1-based line and column at which the span starts, resolved with `index`.
+    ///
+    /// Columns are counted in `char`s.
+    pub fn line_col_with(&self, index: &LineIndex) -> (usize, usize) {
+        index.line_col(self.span().start)
+    }
+
+    /// Returns an adapter which displays the wrapped value followed by its `line:col` position,
+    /// resolved with `index`.
+    ///
+    /// Unlike [`Spanned::located`], which shows the byte span, this shows the same position as
+    /// [`Sliced::located`], without scanning the source each time it's displayed.
+    pub fn located_with<'a>(&'a self, index: &'a LineIndex<'a>) -> impl Display + 'a
+    where
+        T: Display,
+    {
+        LocatedWith(self, index)
+    }
+}
+
+/// Displays a `Spanned` value followed by its `line:col` position, resolved with a [`LineIndex`].
+struct LocatedWith<'a, T>(&'a Spanned<T>, &'a LineIndex<'a>);
+
+impl<T: Display> Display for LocatedWith<'_, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (line, col) = self.0.line_col_with(self.1);
+        write!(f, "{} ({}:{})", self.0.inner, line, col)
+    }
+}
+
+impl<T> Sliced<'_, T> {
+    /// The 1-based line and column at which the slice starts, resolved with `index`.
+    ///
+    /// `index` should be built from the same source as `self`. This gives the same result as
+    /// [`Sliced::line_col`], without scanning the source.
+    pub fn line_col_with(&self, index: &LineIndex) -> (usize, usize) {
+        index.line_col(self.span().start)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::span::line_col;
+
+    /// A few kilobytes of lines of varying lengths, including empty lines and multi-byte chars.
+    fn large_source() -> String {
+        (0..1000)
+            .map(|i| match i % 5 {
+                0 => String::new(),
+                1 => "é".repeat(i % 13),
+                2 => format!("let x{} = \"名前\";", i),
+                _ => "ab".repeat(i % 7),
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn line_col_matches_a_naive_scan() {
+        let source = large_source();
+        assert!(source.len() > 4096);
+        let index = LineIndex::new(&source);
+        for offset in 0..=source.len() + 2 {
+            assert_eq!(
+                index.line_col(offset),
+                line_col(&source, offset),
+                "at {}",
+                offset
+            );
+        }
+    }
+
+    #[test]
+    fn line_count_counts_the_line_after_a_trailing_newline() {
+        assert_eq!(LineIndex::new("").line_count(), 1);
+        assert_eq!(LineIndex::new("a\nb").line_count(), 2);
+        assert_eq!(LineIndex::new("a\nb\n").line_count(), 3);
+        assert_eq!(LineIndex::new(&large_source()).line_count(), 1000);
+    }
+
+    #[test]
+    fn resolves_positions_of_spanned_and_sliced_values() {
+        let source = "fn main() {\n    let x = 1;\n}";
+        let index = LineIndex::new(source);
+        let sliced = Sliced::new('x', 20..21, source);
+        assert_eq!(sliced.line_col_with(&index), (2, 9));
+        assert_eq!(sliced.line_col_with(&index), sliced.line_col());
+
+        let spanned = Spanned::new('x', 20..21);
+        assert_eq!(spanned.line_col_with(&index), (2, 9));
+        assert_eq!(spanned.located_with(&index).to_string(), "x (2:9)");
+    }
+
+    #[test]
+    fn supports_an_empty_source() {
+        let index = LineIndex::new("");
+        assert_eq!(index.line_col(0), (1, 1));
+        assert_eq!(index.line_col(3), (1, 1));
+        assert_eq!(Sliced::new((), 0..0, "").line_col_with(&index), (1, 1));
+    }
+
+    #[test]
+    fn located_with_formats_items_through_a_shared_index() {
+        let source = "let x = 1;\nlet é = 2; x\n";
+        let index = LineIndex::new(source);
+        let items = [
+            Spanned::new("x", 4..5),
+            Spanned::new("é", 15..17),
+            Spanned::new("x", 23..24),
+        ];
+        let formatted: Vec<String> = items
+            .iter()
+            .map(|item| item.located_with(&index).to_string())
+            .collect();
+        assert_eq!(formatted, ["x (1:5)", "é (2:5)", "x (2:12)"]);
+        for item in &items {
+            let sliced = Sliced::new(item.inner, item.span(), source);
+            assert_eq!(
+                item.located_with(&index).to_string(),
+                sliced.located().to_string()
+            );
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use lsp_types::{Position, Range};
+
+use crate::{Sliced, Span, Spanned};
+
+/// Converts the byte `offset` in `source` to a zero-based LSP position, counting characters in
+/// UTF-16 code units.
+///
+/// Offsets past the end of `source` are clamped to its length, and offsets inside a `char` are
+/// moved back to the start of that `char`.
+fn lsp_position(source: &str, offset: usize) -> Position {
+    let mut offset = offset.min(source.len());
+    while !source.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    let before = &source[..offset];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    let line = before[..line_start].matches('\n').count();
+    let character = before[line_start..].encode_utf16().count();
+    Position::new(
+        u32::try_from(line).unwrap_or(u32::MAX),
+        u32::try_from(character).unwrap_or(u32::MAX),
+    )
+}
+
+fn lsp_range(span: Span, source: &str) -> Range {
+    Range::new(
+        lsp_position(source, span.start),
+        lsp_position(source, span.end),
+    )
+}
+
+impl<T> Spanned<T> {
+    /// Converts the span to an LSP range in `source`.
+    ///
+    /// Lines and characters are zero-based, and characters are counted in UTF-16 code units as
+    /// required by the LSP specification.
+    pub fn to_lsp_range(&self, source: &str) -> Range {
+        lsp_range(self.span(), source)
+    }
+}
+
+impl<T> Sliced<'_, T> {
+    /// Converts the span to an LSP range in the source.
+    ///
+    /// Lines and characters are zero-based, and characters are counted in UTF-16 code units as
+    /// required by the LSP specification.
+    pub fn to_lsp_range(&self) -> Range {
+        lsp_range(self.span(), self.source())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_surrogate_pairs_as_two_utf16_units() {
+        let source = "a😀b\n😀x";
+        assert_eq!(
+            Spanned::new((), 5..6).to_lsp_range(source),
+            Range::new(Position::new(0, 3), Position::new(0, 4))
+        );
+        assert_eq!(
+            Spanned::new((), 7..12).to_lsp_range(source),
+            Range::new(Position::new(1, 0), Position::new(1, 3))
+        );
+    }
+
+    #[test]
+    fn counts_other_multi_byte_chars_as_one_utf16_unit() {
+        let sliced = Sliced::new((), 2..4, "éé=");
+        assert_eq!(
+            sliced.to_lsp_range(),
+            Range::new(Position::new(0, 1), Position::new(0, 2))
+        );
+    }
+
+    #[test]
+    fn supports_an_empty_source() {
+        let start = Position::new(0, 0);
+        assert_eq!(
+            Sliced::new((), 0..0, "").to_lsp_range(),
+            Range::new(start, start)
+        );
+        assert_eq!(
+            Spanned::new((), 0..0).to_lsp_range(""),
+            Range::new(start, start)
+        );
+    }
+}
@@ -0,0 +1,65 @@
+/// Asserts that two [`Spanned`](struct@crate::Spanned) or [`Sliced`](struct@crate::Sliced)
+/// values are equal, reporting whether the values, the spans, or both differ on failure.
+///
+/// The wrapped values and spans are compared using `inner` and `span()`, so the wrapped type
+/// must implement `PartialEq` and `Debug`.
+#[macro_export]
+macro_rules! assert_spanned_eq {
+    ($actual:expr, $expected:expr $(,)?) => {
+        match (&$actual, &$expected) {
+            (actual, expected) => {
+                let (actual_span, expected_span) = (actual.span(), expected.span());
+                match (actual.inner == expected.inner, actual_span == expected_span) {
+                    (true, true) => {}
+                    (false, true) => panic!(
+                        "assertion failed: values differ\n  actual value: {:?}\nexpected value: {:?}\n          span: {:?}",
+                        actual.inner, expected.inner, actual_span,
+                    ),
+                    (true, false) => panic!(
+                        "assertion failed: spans differ\n  actual span: {:?}\nexpected span: {:?}\n        value: {:?}",
+                        actual_span, expected_span, actual.inner,
+                    ),
+                    (false, false) => panic!(
+                        "assertion failed: values and spans differ\n  actual value: {:?}\nexpected value: {:?}\n   actual span: {:?}\n expected span: {:?}",
+                        actual.inner, expected.inner, actual_span, expected_span,
+                    ),
+                }
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Sliced, Spanned};
+
+    #[test]
+    fn passes_for_equal_values_and_spans() {
+        crate::assert_spanned_eq!(Spanned::new(1, 0..1), Spanned::new(1, 0..1));
+        crate::assert_spanned_eq!(Sliced::new(1, 0..1, "a"), Spanned::new(1, 0..1));
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "assertion failed: values differ\n  actual value: 1\nexpected value: 2\n          span: 0..1"
+    )]
+    fn reports_a_value_only_mismatch() {
+        crate::assert_spanned_eq!(Spanned::new(1, 0..1), Spanned::new(2, 0..1));
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "assertion failed: spans differ\n  actual span: 0..1\nexpected span: 2..3\n        value: 1"
+    )]
+    fn reports_a_span_only_mismatch() {
+        crate::assert_spanned_eq!(Spanned::new(1, 0..1), Spanned::new(1, 2..3));
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "assertion failed: values and spans differ\n  actual value: 1\nexpected value: 2\n   actual span: 0..1\n expected span: 2..3"
+    )]
+    fn reports_a_value_and_span_mismatch() {
+        crate::assert_spanned_eq!(Spanned::new(1, 0..1), Spanned::new(2, 2..3));
+    }
+}
@@ -0,0 +1,38 @@
+use crate::Sliced;
+
+/// Converts a `pest` span into a `Sliced` over the full input, wrapping the spanned text.
+impl<'i> From<::pest::Span<'i>> for Sliced<'i, &'i str> {
+    fn from(value: ::pest::Span<'i>) -> Self {
+        Sliced::new(
+            value.as_str(),
+            value.start()..value.end(),
+            value.get_input(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    enum Rule {
+        Keyword,
+    }
+
+    #[test]
+    fn converts_the_span_of_a_parsed_pair() {
+        let input = "let x";
+        let pairs = ::pest::state(input, |state| {
+            state.rule(Rule::Keyword, |state| state.match_string("let"))
+        })
+        .unwrap();
+        let pair = pairs.into_iter().next().unwrap();
+        assert_eq!(pair.as_rule(), Rule::Keyword);
+
+        let sliced = Sliced::from(pair.as_span());
+        assert_eq!(sliced.inner, "let");
+        assert_eq!(sliced.span(), 0..3);
+        assert_eq!(sliced.source(), input);
+    }
+}
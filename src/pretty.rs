@@ -0,0 +1,107 @@
+use std::fmt::Write;
+
+use crate::{
+    span::{char_count, line_bounds, line_col},
+    Sliced,
+};
+
+impl<T> Sliced<'_, T> {
+    /// Renders the lines of the source containing the slice, with the slice underlined and
+    /// labelled with `message`, in the style of `rustc`'s diagnostics.
+    ///
+    /// ```text
+    ///  --> 2:5
+    ///   |
+    /// 2 | let x = 1;
+    ///   |     ^ message
+    /// ```
+    ///
+    /// Each line of a multi-line slice is underlined separately, with `message` attached to the
+    /// last line. An empty slice is marked by a single caret.
+    pub fn pretty(&self, message: &str) -> String {
+        let source = self.source();
+        let segments = self.line_spans();
+        let (first_line, first_col) = line_col(source, self.span().start);
+        let last_line = line_col(source, segments[segments.len() - 1].start).0;
+        let gutter = " ".repeat(last_line.to_string().len());
+
+        let mut out = String::new();
+        let _ = writeln!(out, "{} --> {}:{}", gutter, first_line, first_col);
+        let _ = write!(out, "{} |", gutter);
+        for (i, segment) in segments.iter().enumerate() {
+            let line = line_bounds(source, segment.start);
+            let bytes = source.as_bytes();
+            let padding = char_count(&bytes[line.start..segment.start]);
+            let carets = char_count(&bytes[segment.clone()]).max(1);
+            let _ = write!(
+                out,
+                "\n{:>width$} | {}\n{} | {}{}",
+                first_line + i,
+                &source[line],
+                gutter,
+                " ".repeat(padding),
+                "^".repeat(carets),
+                width = gutter.len(),
+            );
+        }
+        if !message.is_empty() {
+            let _ = write!(out, " {}", message);
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_a_single_line_span() {
+        let source = "fn main() {\n    let x = 1;\n}";
+        let sliced = Sliced::new((), 20..21, source);
+        assert_eq!(
+            sliced.pretty("unused variable"),
+            concat!(
+                "  --> 2:9\n",
+                "  |\n",
+                "2 |     let x = 1;\n",
+                "  |         ^ unused variable",
+            )
+        );
+    }
+
+    #[test]
+    fn underlines_each_line_of_a_multi_line_span() {
+        let source = "let s = \"a\nbcd\";";
+        let sliced = Sliced::new((), 8..15, source);
+        assert_eq!(
+            sliced.pretty("unterminated"),
+            concat!(
+                "  --> 1:9\n",
+                "  |\n",
+                "1 | let s = \"a\n",
+                "  |         ^^\n",
+                "2 | bcd\";\n",
+                "  | ^^^^ unterminated",
+            )
+        );
+    }
+
+    #[test]
+    fn widens_the_gutter_for_long_line_numbers() {
+        let source = "\n".repeat(9) + "x";
+        let sliced = Sliced::new((), 9..10, &source);
+        assert_eq!(
+            sliced.pretty(""),
+            concat!("   --> 10:1\n", "   |\n", "10 | x\n", "   | ^")
+        );
+    }
+
+    #[test]
+    fn supports_an_empty_source() {
+        assert_eq!(
+            Sliced::new((), 0..0, "").pretty("empty file"),
+            concat!("  --> 1:1\n", "  |\n", "1 | \n", "  | ^ empty file")
+        );
+    }
+}
@@ -0,0 +1,61 @@
+use ::regex::{Captures, Match, Regex};
+
+use crate::Sliced;
+
+impl<'h> Sliced<'h, &'h str> {
+    /// Creates a new `Sliced` value from a regex match, wrapping the matched text.
+    ///
+    /// # Parameters
+    /// - `m`: The match to be converted.
+    /// - `haystack`: The text which was searched to produce `m`.
+    pub fn from_match(m: Match<'h>, haystack: &'h str) -> Self {
+        Sliced::new(m.as_str(), m.range(), haystack)
+    }
+
+    /// Converts every named group in `captures` which participated in the match into a `Sliced`
+    /// value, paired with the group's name.
+    ///
+    /// # Parameters
+    /// - `regex`: The regex which produced `captures`.
+    /// - `captures`: The captures to be converted.
+    /// - `haystack`: The text which was searched to produce `captures`.
+    pub fn from_named_captures<'r>(
+        regex: &'r Regex,
+        captures: &Captures<'h>,
+        haystack: &'h str,
+    ) -> Vec<(&'r str, Self)> {
+        regex
+            .capture_names()
+            .flatten()
+            .filter_map(|name| Some((name, Self::from_match(captures.name(name)?, haystack))))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_match_slices_the_haystack() {
+        let haystack = "let answer = 42;";
+        let m = Regex::new(r"\d+").unwrap().find(haystack).unwrap();
+        let sliced = Sliced::from_match(m, haystack);
+        assert_eq!(sliced.inner, "42");
+        assert_eq!(sliced.span(), 13..15);
+        assert_eq!(sliced.slice(), "42");
+        assert_eq!(sliced.source(), haystack);
+    }
+
+    #[test]
+    fn from_named_captures_skips_groups_which_did_not_match() {
+        let haystack = "let answer = 42;";
+        let regex = Regex::new(r"let (?P<name>\w+)(?P<ty>: \w+)? = (?P<value>\d+)").unwrap();
+        let captures = regex.captures(haystack).unwrap();
+        let groups: Vec<(&str, &str, _)> = Sliced::from_named_captures(&regex, &captures, haystack)
+            .into_iter()
+            .map(|(name, sliced)| (name, sliced.slice(), sliced.span()))
+            .collect();
+        assert_eq!(groups, [("name", "answer", 4..10), ("value", "42", 13..15)]);
+    }
+}
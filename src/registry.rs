@@ -0,0 +1,99 @@
+use std::{collections::HashMap, hash::Hash};
+
+use crate::Span;
+
+/// The spans registered for a single key of a [`SpanRegistry`].
+#[derive(Debug, Clone, PartialEq)]
+struct Entry {
+    definition: Option<Span>,
+    references: Vec<Span>,
+}
+
+/// Maps keys, such as identifiers, to the span of their definition and the spans of every
+/// reference to them.
+///
+/// This is the basis of features like "go to definition" and "find all references".
+#[derive(Debug, Clone)]
+pub struct SpanRegistry<K> {
+    entries: HashMap<K, Entry>,
+}
+
+impl<K: Eq + Hash> SpanRegistry<K> {
+    /// Creates a new, empty `SpanRegistry`.
+    pub fn new() -> Self {
+        Self {
+            entries: HashMap::new(),
+        }
+    }
+
+    fn entry(&mut self, key: K) -> &mut Entry {
+        self.entries.entry(key).or_insert_with(|| Entry {
+            definition: None,
+            references: Vec::new(),
+        })
+    }
+
+    /// Records `span` as the definition of `key`, returning the previous definition if there
+    /// was one.
+    pub fn define(&mut self, key: K, span: Span) -> Option<Span> {
+        self.entry(key).definition.replace(span)
+    }
+
+    /// Records `span` as a reference to `key`.
+    ///
+    /// References may be recorded before the definition.
+    pub fn reference(&mut self, key: K, span: Span) {
+        self.entry(key).references.push(span);
+    }
+
+    /// The span of the definition of `key`, if it has been defined.
+    pub fn definition(&self, key: &K) -> Option<Span> {
+        self.entries.get(key)?.definition.clone()
+    }
+
+    /// The spans of every reference to `key`, in the order they were recorded.
+    pub fn references(&self, key: &K) -> &[Span] {
+        self.entries
+            .get(key)
+            .map_or(&[], |entry| entry.references.as_slice())
+    }
+}
+
+impl<K: Eq + Hash> Default for SpanRegistry<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_a_definition_and_its_references() {
+        let mut registry = SpanRegistry::new();
+        registry.reference("x", 20..21);
+        assert_eq!(registry.define("x", 4..5), None);
+        registry.reference("x", 12..13);
+        registry.reference("y", 30..31);
+
+        assert_eq!(registry.definition(&"x"), Some(4..5));
+        assert_eq!(registry.references(&"x"), [20..21, 12..13]);
+        assert_eq!(registry.definition(&"y"), None);
+    }
+
+    #[test]
+    fn redefining_returns_the_previous_definition() {
+        let mut registry = SpanRegistry::new();
+        registry.define("x", 4..5);
+        assert_eq!(registry.define("x", 9..10), Some(4..5));
+        assert_eq!(registry.definition(&"x"), Some(9..10));
+    }
+
+    #[test]
+    fn unknown_keys_have_no_spans() {
+        let registry: SpanRegistry<&str> = SpanRegistry::default();
+        assert_eq!(registry.definition(&"z"), None);
+        assert!(registry.references(&"z").is_empty());
+    }
+}
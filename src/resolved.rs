@@ -0,0 +1,101 @@
+use std::ops::{Deref, DerefMut};
+
+use crate::{Sliced, Span};
+
+/// A [`struct@Sliced`] value which has already looked up its slice of the source, so that
+/// reading the slice doesn't index the source again.
+///
+/// Created by [`Sliced::resolve`], and useful for values whose slice is read many times, such as
+/// in a rendering loop.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedSliced<'source, T> {
+    pub inner: T,
+    span: Span,
+    slice: &'source str,
+    source: &'source str,
+}
+
+impl<'source, T> Sliced<'source, T> {
+    /// Looks up the slice once, storing it alongside the value, span and source.
+    ///
+    /// # Panics
+    /// Panics if the span is not valid for the source, see [`Sliced::validate`].
+    pub fn resolve(self) -> ResolvedSliced<'source, T> {
+        let slice = self.slice();
+        let (inner, span, source) = self.into_parts();
+        ResolvedSliced {
+            inner,
+            span,
+            slice,
+            source,
+        }
+    }
+}
+
+impl<'source, T> ResolvedSliced<'source, T> {
+    /// The slice associated with the wrapped value, without indexing the source.
+    pub fn slice(&self) -> &'source str {
+        self.slice
+    }
+
+    /// The span associated with the wrapped value.
+    pub fn span(&self) -> Span {
+        self.span.clone()
+    }
+
+    /// The source string that the slice is a part of.
+    pub fn source(&self) -> &'source str {
+        self.source
+    }
+
+    /// Consume `self` and return the inner, wrapped value.
+    pub fn unwrap(self) -> T {
+        self.inner
+    }
+
+    /// Converts back into a `Sliced` value, discarding the stored slice.
+    pub fn into_sliced(self) -> Sliced<'source, T> {
+        Sliced::new(self.inner, self.span, self.source)
+    }
+}
+
+impl<T> Deref for ResolvedSliced<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl<T> DerefMut for ResolvedSliced<'_, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.inner
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolve_stores_the_same_slice() {
+        let source = "let 名前 = 1;";
+        let sliced = Sliced::new(7u8, 4..10, source);
+        let resolved = sliced.clone().resolve();
+        assert_eq!(resolved.slice(), sliced.slice());
+        assert_eq!((resolved.span(), resolved.source()), (4..10, source));
+        assert_eq!(*resolved, 7);
+        assert_eq!(resolved.into_sliced(), sliced);
+    }
+
+    #[test]
+    fn repeated_reads_return_the_stored_slice() {
+        let mut resolved = Sliced::new(0, 4..7, "let abc;").resolve();
+        let first = resolved.slice();
+        for _ in 0..3 {
+            *resolved += 1;
+            assert!(std::ptr::eq(resolved.slice(), first));
+        }
+        assert_eq!((first, resolved.unwrap()), ("abc", 3));
+    }
+}
@@ -0,0 +1,843 @@
+use std::{cmp::Reverse, collections::BTreeMap};
+
+use crate::{
+    span::{contains, overlaps},
+    span_union, LineIndex, Span, SpanError, Spanned,
+};
+
+/// Collects spanned results, keeping every error rather than stopping at the first.
+///
+/// Returns `Ok` with every value if all items succeeded, otherwise `Err` with every error. Each
+/// value or error keeps its own span.
+pub fn collect_results<T, E>(
+    items: impl IntoIterator<Item = Spanned<Result<T, E>>>,
+) -> Result<Vec<Spanned<T>>, Vec<Spanned<E>>> {
+    let mut oks = Vec::new();
+    let mut errs = Vec::new();
+    for item in items {
+        match item.unzip() {
+            Ok(t) if errs.is_empty() => oks.push(t),
+            Ok(_) => {}
+            Err(e) => errs.push(e),
+        }
+    }
+    if errs.is_empty() {
+        Ok(oks)
+    } else {
+        Err(errs)
+    }
+}
+
+/// Lifts a list of spanned results into a result of spanned lists.
+///
+/// This is [`collect_results`] specialised to a `Vec`: every value or error keeps its own span,
+/// and all errors are returned if any item failed.
+pub fn lift<T, E>(items: Vec<Spanned<Result<T, E>>>) -> Result<Vec<Spanned<T>>, Vec<Spanned<E>>> {
+    collect_results(items)
+}
+
+/// Groups spanned items by the 1-based line of `source` on which their span starts.
+///
+/// Items whose span crosses several lines are keyed by the line they start on. Items within a
+/// line keep their relative order.
+pub fn group_by_line<T>(items: Vec<Spanned<T>>, source: &str) -> BTreeMap<usize, Vec<Spanned<T>>> {
+    let index = LineIndex::new(source);
+    let mut lines: BTreeMap<usize, Vec<Spanned<T>>> = BTreeMap::new();
+    for item in items {
+        let (line, _) = index.line_col(item.span().start);
+        lines.entry(line).or_default().push(item);
+    }
+    lines
+}
+
+/// Returns the text of `source` in the gaps around each of `items`.
+///
+/// `items` must be sorted by position. The result has one more element than `items`: the text
+/// before the first item, between each pair of adjacent items, and after the last item. Gaps
+/// which are empty, inverted by overlapping items, or out of bounds are returned as `""`.
+pub fn gaps<'s, T>(items: &[Spanned<T>], source: &'s str) -> Vec<&'s str> {
+    let mut gaps = Vec::with_capacity(items.len() + 1);
+    let mut end = 0;
+    for item in items {
+        let span = item.span();
+        gaps.push(source.get(end..span.start).unwrap_or(""));
+        end = span.end;
+    }
+    gaps.push(source.get(end..).unwrap_or(""));
+    gaps
+}
+
+/// Pairs up two equally long lists of spanned values, merging the spans of each pair.
+///
+/// Each result spans the range covering both of its inputs. Returns `None` if the lists have
+/// different lengths.
+pub fn zip_spanned<A, B>(a: Vec<Spanned<A>>, b: Vec<Spanned<B>>) -> Option<Vec<Spanned<(A, B)>>> {
+    if a.len() != b.len() {
+        return None;
+    }
+    let zipped = a
+        .into_iter()
+        .zip(b)
+        .map(|(a, b)| {
+            let span = span_union(&[a.span(), b.span()]);
+            Spanned::new((a.unwrap(), b.unwrap()), span)
+        })
+        .collect();
+    Some(zipped)
+}
+
+/// Iterates over each pair of adjacent items along with the span of the gap between them.
+///
+/// The gap of a pair is `prev.end..next.start`, which is empty if the items touch. If the items
+/// overlap the gap is the empty span `prev.end..prev.end`.
+pub fn adjacent_pairs<T>(
+    items: &[Spanned<T>],
+) -> impl Iterator<Item = (&Spanned<T>, &Spanned<T>, Span)> {
+    items.windows(2).map(|pair| {
+        let (prev, next) = (&pair[0], &pair[1]);
+        let end = prev.span().end;
+        (prev, next, end..end.max(next.span().start))
+    })
+}
+
+/// Merges runs of consecutive items which have equal values and touching spans.
+///
+/// Two consecutive items are merged into one spanning both when their values are equal and the
+/// first ends exactly where the second starts. Equal items separated by a gap are kept apart.
+pub fn coalesce<T: PartialEq>(items: Vec<Spanned<T>>) -> Vec<Spanned<T>> {
+    let mut merged: Vec<Spanned<T>> = Vec::with_capacity(items.len());
+    for item in items {
+        match merged.last_mut() {
+            Some(last) if last.inner == item.inner && last.span().end == item.span().start => {
+                last.set_span(last.span().start..item.span().end);
+            }
+            _ => merged.push(item),
+        }
+    }
+    merged
+}
+
+/// Combines two optional spanned values into one spanning whichever of them are present.
+///
+/// Returns `None` only if both are absent.
+pub fn zip_optional_spanned<A, B>(
+    a: Option<Spanned<A>>,
+    b: Option<Spanned<B>>,
+) -> Option<Spanned<(Option<A>, Option<B>)>> {
+    let spans: Vec<Span> = a
+        .iter()
+        .map(Spanned::span)
+        .chain(b.iter().map(Spanned::span))
+        .collect();
+    if spans.is_empty() {
+        return None;
+    }
+    Some(Spanned::new(
+        (a.map(Spanned::unwrap), b.map(Spanned::unwrap)),
+        span_union(&spans),
+    ))
+}
+
+/// Applies `f` to each pair of values from two equally long slices of spanned values.
+///
+/// Each result spans the range covering both of its inputs. Returns `None` if the slices have
+/// different lengths.
+pub fn zip_map<A, B, R>(
+    a: &[Spanned<A>],
+    b: &[Spanned<B>],
+    f: impl Fn(&A, &B) -> R,
+) -> Option<Vec<Spanned<R>>> {
+    if a.len() != b.len() {
+        return None;
+    }
+    let mapped = a
+        .iter()
+        .zip(b)
+        .map(|(a, b)| Spanned::new(f(&a.inner, &b.inner), span_union(&[a.span(), b.span()])))
+        .collect();
+    Some(mapped)
+}
+
+/// Keeps only the items whose span lies entirely within `region`.
+///
+/// An item is kept if its span starts at or after the start of `region` and ends at or before
+/// its end, so an empty span at either end of `region` is kept. See [`overlapping`] for keeping
+/// items which only partially lie within `region`.
+pub fn within<T>(items: Vec<Spanned<T>>, region: &Span) -> Vec<Spanned<T>> {
+    items
+        .into_iter()
+        .filter(|item| contains(region, &item.span()))
+        .collect()
+}
+
+/// Keeps only the items whose span shares at least one position with `region`.
+///
+/// Unlike [`within`], items straddling either end of `region` are kept. As an empty span has no
+/// positions, it never overlaps `region`, and an empty `region` keeps no items.
+pub fn overlapping<T>(items: Vec<Spanned<T>>, region: &Span) -> Vec<Spanned<T>> {
+    items
+        .into_iter()
+        .filter(|item| overlaps(region, &item.span()))
+        .collect()
+}
+
+/// Returns the index of the first item which ends after `pos`, using a binary search.
+///
+/// `items` must be sorted by position and must not overlap, so that every item before the
+/// returned index ends at or before `pos` and every item from it onwards ends after `pos`.
+pub fn partition_by_span<T>(items: &[Spanned<T>], pos: usize) -> usize {
+    items.partition_point(|item| item.span().end <= pos)
+}
+
+/// Returns the item whose span contains the byte `pos`, using a binary search.
+///
+/// `items` must be sorted by position and must not overlap, see [`partition_by_span`]. As empty
+/// spans contain no positions, they are never returned.
+pub fn first_at<T>(items: &[Spanned<T>], pos: usize) -> Option<&Spanned<T>> {
+    items
+        .get(partition_by_span(items, pos))
+        .filter(|item| item.span().start <= pos)
+}
+
+/// Removes every item whose span lies entirely within the span of another item.
+///
+/// Of several items with equal spans, only the first is kept. Partially overlapping and
+/// disjoint items are all kept, in their original order.
+pub fn keep_widest<T>(items: Vec<Spanned<T>>) -> Vec<Spanned<T>> {
+    let spans: Vec<Span> = items.iter().map(Spanned::span).collect();
+    // In this order every item which could contain another comes before it, with equal spans
+    // in their original order, so an item is covered if an earlier one reaches as far.
+    let mut order: Vec<usize> = (0..spans.len()).collect();
+    order.sort_by_key(|&i| (spans[i].start, Reverse(spans[i].end), i));
+    let mut covered = vec![false; spans.len()];
+    let mut max_end = None;
+    for i in order {
+        covered[i] = max_end.is_some_and(|end| spans[i].end <= end);
+        max_end = max_end.max(Some(spans[i].end));
+    }
+    items
+        .into_iter()
+        .zip(covered)
+        .filter(|&(_, covered)| !covered)
+        .map(|(item, _)| item)
+        .collect()
+}
+
+/// Combines three spanned values into one spanned tuple.
+///
+/// The result spans the smallest range covering all three inputs, whatever order they appear
+/// in, including any gaps between them.
+pub fn zip3<A, B, C>(a: Spanned<A>, b: Spanned<B>, c: Spanned<C>) -> Spanned<(A, B, C)> {
+    let span = span_union(&[a.span(), b.span(), c.span()]);
+    Spanned::new((a.unwrap(), b.unwrap(), c.unwrap()), span)
+}
+
+/// Combines a list of spanned values into one spanned list.
+///
+/// The result spans the smallest range covering every input, or `0..0` if there are none.
+pub fn zip_all<T>(items: Vec<Spanned<T>>) -> Spanned<Vec<T>> {
+    let spans: Vec<Span> = items.iter().map(Spanned::span).collect();
+    Spanned::new(
+        items.into_iter().map(Spanned::unwrap).collect(),
+        span_union(&spans),
+    )
+}
+
+/// Sorts spanned items in place by span start, then by span end.
+///
+/// The sort is stable, so items with equal spans keep their relative order, and `T` need not
+/// be ordered.
+pub fn sort_by_span<T>(items: &mut [Spanned<T>]) {
+    items.sort_by_key(|item| (item.span().start, item.span().end));
+}
+
+/// Sorts spanned items in place by span start, then by span end, in descending order.
+///
+/// The sort is stable, so items with equal spans keep their relative order.
+pub fn sort_by_span_desc<T>(items: &mut [Spanned<T>]) {
+    items.sort_by_key(|item| std::cmp::Reverse((item.span().start, item.span().end)));
+}
+
+/// Checks the span of every item against `source`, returning the index and error of each item
+/// whose span is invalid.
+///
+/// Returns an empty list if every span is valid, see [`Spanned::validate`].
+pub fn validate_all<T>(items: &[Spanned<T>], source: &str) -> Vec<(usize, SpanError)> {
+    items
+        .iter()
+        .enumerate()
+        .filter_map(|(i, item)| item.validate(source).err().map(|err| (i, err)))
+        .collect()
+}
+
+/// Converts the spans of `items`, relative to a fragment of a source starting at `origin`, into
+/// absolute spans, see [`Spanned::anchored`].
+///
+/// # Panics
+/// Panics if either end of any span overflows.
+pub fn absolutize<T>(items: Vec<Spanned<T>>, origin: usize) -> Vec<Spanned<T>> {
+    items
+        .into_iter()
+        .map(|item| item.anchored(origin))
+        .collect()
+}
+
+/// Indexes spanned items by the start of their span, for lookup and ordered iteration by
+/// position.
+///
+/// Of several items starting at the same position, the last is kept.
+pub fn index_by_start<T>(items: Vec<Spanned<T>>) -> BTreeMap<usize, Spanned<T>> {
+    items
+        .into_iter()
+        .map(|item| (item.span().start, item))
+        .collect()
+}
+
+/// Iterates, in order of position, over the items of an index built by [`index_by_start`] whose
+/// span starts within `region`.
+///
+/// An empty or inverted `region` yields no items.
+pub fn range_query<'a, T>(
+    index: &'a BTreeMap<usize, Spanned<T>>,
+    region: &Span,
+) -> impl Iterator<Item = &'a Spanned<T>> {
+    index
+        .range(region.start..region.end.max(region.start))
+        .map(|(_, item)| item)
+}
+
+/// Returns the item with the shortest span, see [`Spanned::cmp_by_len`].
+///
+/// Of several equally short items, the one starting first is returned, and then the first of
+/// those in `items`. Returns `None` if `items` is empty.
+pub fn shortest<T>(items: &[Spanned<T>]) -> Option<&Spanned<T>> {
+    items.iter().min_by(|a, b| a.cmp_by_len(b))
+}
+
+/// Returns the item with the longest span, see [`Spanned::cmp_by_len`].
+///
+/// Of several equally long items, the one starting first is returned, and then the first of
+/// those in `items`. Returns `None` if `items` is empty.
+pub fn longest<T>(items: &[Spanned<T>]) -> Option<&Spanned<T>> {
+    items.iter().min_by(|a, b| {
+        let (a, b) = (a.span(), b.span());
+        b.len().cmp(&a.len()).then(a.start.cmp(&b.start))
+    })
+}
+
+/// Merges two lists of spanned items, each already sorted by span start, into one list sorted
+/// by span start.
+///
+/// Both `a` and `b` must be sorted by span start, otherwise the result isn't sorted either.
+/// Items starting at the same position keep their relative order, with those from `a` first.
+pub fn merge_sorted<T>(a: Vec<Spanned<T>>, b: Vec<Spanned<T>>) -> Vec<Spanned<T>> {
+    let mut merged = Vec::with_capacity(a.len() + b.len());
+    let mut a = a.into_iter().peekable();
+    let mut b = b.into_iter().peekable();
+    while let (Some(x), Some(y)) = (a.peek(), b.peek()) {
+        let next = if y.span().start < x.span().start {
+            b.next()
+        } else {
+            a.next()
+        };
+        merged.extend(next);
+    }
+    merged.extend(a);
+    merged.extend(b);
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spans<T>(items: &[Spanned<T>]) -> Vec<Span> {
+        items.iter().map(Spanned::span).collect()
+    }
+
+    #[test]
+    fn keep_widest_drops_contained_items() {
+        let items = vec![
+            Spanned::new('a', 2..4),
+            Spanned::new('b', 0..10),
+            Spanned::new('c', 8..12),
+            Spanned::new('d', 10..10),
+        ];
+        let kept = keep_widest(items);
+        assert_eq!(spans(&kept), [0..10, 8..12]);
+        assert_eq!(kept[0].inner, 'b');
+    }
+
+    #[test]
+    fn keep_widest_keeps_first_of_equal_spans() {
+        let items = vec![
+            Spanned::new('a', 1..3),
+            Spanned::new('b', 1..3),
+            Spanned::new('c', 1..3),
+        ];
+        let kept = keep_widest(items);
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].inner, 'a');
+    }
+
+    #[test]
+    fn keep_widest_matches_pairwise_check() {
+        let mut state = 1u64;
+        let mut next = |bound: u64| {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (state >> 33) % bound
+        };
+        for _ in 0..200 {
+            let items: Vec<Spanned<usize>> = (0..next(12) as usize)
+                .map(|i| {
+                    let start = next(10) as usize;
+                    Spanned::new(i, start..start + next(6) as usize)
+                })
+                .collect();
+            let all = spans(&items);
+            let expected: Vec<usize> = (0..all.len())
+                .filter(|&i| {
+                    !(0..all.len()).any(|j| {
+                        j != i && contains(&all[j], &all[i]) && (all[j] != all[i] || j < i)
+                    })
+                })
+                .collect();
+            let kept: Vec<usize> = keep_widest(items)
+                .into_iter()
+                .map(Spanned::unwrap)
+                .collect();
+            assert_eq!(kept, expected);
+        }
+    }
+
+    #[test]
+    fn collect_results_keeps_every_value() {
+        let items: Vec<Spanned<Result<i32, char>>> =
+            vec![Spanned::new(Ok(1), 0..1), Spanned::new(Ok(2), 2..3)];
+        assert_eq!(
+            collect_results(items),
+            Ok(vec![Spanned::new(1, 0..1), Spanned::new(2, 2..3)])
+        );
+    }
+
+    #[test]
+    fn collect_results_keeps_a_single_error() {
+        let items: Vec<Spanned<Result<i32, char>>> =
+            vec![Spanned::new(Ok(1), 0..1), Spanned::new(Err('x'), 2..3)];
+        assert_eq!(collect_results(items), Err(vec![Spanned::new('x', 2..3)]));
+    }
+
+    #[test]
+    fn collect_results_keeps_every_error() {
+        let items: Vec<Spanned<Result<i32, char>>> = vec![
+            Spanned::new(Err('x'), 0..1),
+            Spanned::new(Ok(1), 2..3),
+            Spanned::new(Err('y'), 4..6),
+        ];
+        assert_eq!(
+            collect_results(items),
+            Err(vec![Spanned::new('x', 0..1), Spanned::new('y', 4..6)])
+        );
+    }
+
+    #[test]
+    fn group_by_line_keys_items_by_their_start_line() {
+        let source = "a b\nc\nd e";
+        let items = vec![
+            Spanned::new('a', 0..1),
+            Spanned::new('d', 6..7),
+            Spanned::new('b', 2..5),
+            Spanned::new('e', 8..9),
+        ];
+        let lines = group_by_line(items, source);
+        assert_eq!(lines.keys().copied().collect::<Vec<_>>(), [1, 3]);
+        assert_eq!(spans(&lines[&1]), [0..1, 2..5]);
+        assert_eq!(spans(&lines[&3]), [6..7, 8..9]);
+    }
+
+    #[test]
+    fn gaps_interleaved_with_slices_reproduce_the_source() {
+        let source = "  let x\t= 1; ";
+        let items = vec![
+            Spanned::new((), 2..5),
+            Spanned::new((), 6..7),
+            Spanned::new((), 8..9),
+            Spanned::new((), 10..11),
+            Spanned::new((), 11..12),
+        ];
+        let gaps = gaps(&items, source);
+        assert_eq!(gaps, ["  ", " ", "\t", " ", "", " "]);
+
+        let mut rebuilt = String::from(gaps[0]);
+        for (item, gap) in items.iter().zip(&gaps[1..]) {
+            rebuilt.push_str(&source[item.span()]);
+            rebuilt.push_str(gap);
+        }
+        assert_eq!(rebuilt, source);
+    }
+
+    #[test]
+    fn gaps_of_no_items_is_the_whole_source() {
+        assert_eq!(gaps::<()>(&[], "abc"), ["abc"]);
+    }
+
+    #[test]
+    fn zip_spanned_pairs_values_and_merges_spans() {
+        let a = vec![Spanned::new("x", 0..1), Spanned::new("y", 4..5)];
+        let b = vec![Spanned::new(1, 0..3), Spanned::new(2, 2..5)];
+        assert_eq!(
+            zip_spanned(a, b),
+            Some(vec![
+                Spanned::new(("x", 1), 0..3),
+                Spanned::new(("y", 2), 2..5)
+            ])
+        );
+    }
+
+    #[test]
+    fn zip_spanned_rejects_lists_of_different_lengths() {
+        let a = vec![Spanned::new("x", 0..1)];
+        let b = vec![Spanned::new(1, 0..1), Spanned::new(2, 2..3)];
+        assert_eq!(zip_spanned(a, b), None);
+    }
+
+    #[test]
+    fn adjacent_pairs_reports_the_gap_between_neighbours() {
+        let items = [
+            Spanned::new('a', 0..2),
+            Spanned::new('b', 2..3),
+            Spanned::new('c', 5..6),
+        ];
+        let gaps: Vec<(char, char, Span)> = adjacent_pairs(&items)
+            .map(|(prev, next, gap)| (prev.inner, next.inner, gap))
+            .collect();
+        assert_eq!(gaps, [('a', 'b', 2..2), ('b', 'c', 3..5)]);
+    }
+
+    #[test]
+    fn adjacent_pairs_gives_an_empty_gap_for_overlapping_items() {
+        let items = [Spanned::new((), 0..4), Spanned::new((), 2..6)];
+        let mut pairs = adjacent_pairs(&items);
+        assert_eq!(pairs.next().map(|(_, _, gap)| gap), Some(4..4));
+        assert!(pairs.next().is_none());
+    }
+
+    #[test]
+    fn coalesce_merges_touching_equal_items() {
+        let items = vec![
+            Spanned::new('+', 0..1),
+            Spanned::new('+', 1..2),
+            Spanned::new('+', 2..3),
+        ];
+        assert_eq!(coalesce(items), [Spanned::new('+', 0..3)]);
+    }
+
+    #[test]
+    fn coalesce_keeps_equal_items_with_a_gap_apart() {
+        let items = vec![Spanned::new('+', 0..1), Spanned::new('+', 2..3)];
+        assert_eq!(
+            coalesce(items),
+            [Spanned::new('+', 0..1), Spanned::new('+', 2..3)]
+        );
+    }
+
+    #[test]
+    fn coalesce_keeps_touching_different_items_apart() {
+        let items = vec![
+            Spanned::new('+', 0..1),
+            Spanned::new('-', 1..2),
+            Spanned::new('-', 2..3),
+        ];
+        assert_eq!(
+            coalesce(items),
+            [Spanned::new('+', 0..1), Spanned::new('-', 1..3)]
+        );
+    }
+
+    #[test]
+    fn zip_optional_spanned_spans_whichever_are_present() {
+        let a = || Some(Spanned::new('a', 0..2));
+        let b = || Some(Spanned::new(1, 5..6));
+        assert_eq!(
+            zip_optional_spanned(a(), b()),
+            Some(Spanned::new((Some('a'), Some(1)), 0..6))
+        );
+        assert_eq!(
+            zip_optional_spanned(a(), None::<Spanned<i32>>),
+            Some(Spanned::new((Some('a'), None), 0..2))
+        );
+        assert_eq!(
+            zip_optional_spanned(None::<Spanned<char>>, b()),
+            Some(Spanned::new((None, Some(1)), 5..6))
+        );
+        assert_eq!(
+            zip_optional_spanned(None::<Spanned<char>>, None::<Spanned<i32>>),
+            None
+        );
+    }
+
+    #[test]
+    fn zip_map_merges_the_span_of_each_pair() {
+        let a = [Spanned::new(1, 0..2), Spanned::new(2, 6..7)];
+        let b = [Spanned::new(10, 1..4), Spanned::new(20, 4..6)];
+        assert_eq!(
+            zip_map(&a, &b, |a, b| a + b),
+            Some(vec![Spanned::new(11, 0..4), Spanned::new(22, 4..7)])
+        );
+    }
+
+    #[test]
+    fn zip_map_rejects_slices_of_different_lengths() {
+        let a = [Spanned::new(1, 0..2)];
+        assert_eq!(zip_map(&a, &[], |a, b: &i32| a + b), None);
+    }
+
+    fn region_tokens() -> Vec<Spanned<char>> {
+        vec![
+            Spanned::new('a', 0..2),
+            Spanned::new('b', 3..5),
+            Spanned::new('c', 5..5),
+            Spanned::new('d', 6..9),
+            Spanned::new('e', 10..12),
+        ]
+    }
+
+    #[test]
+    fn within_keeps_items_entirely_inside_the_region() {
+        let kept = within(region_tokens(), &(3..8));
+        assert_eq!(kept, [Spanned::new('b', 3..5), Spanned::new('c', 5..5)]);
+    }
+
+    #[test]
+    fn overlapping_also_keeps_items_straddling_the_region() {
+        let kept = overlapping(region_tokens(), &(3..8));
+        assert_eq!(kept, [Spanned::new('b', 3..5), Spanned::new('d', 6..9)]);
+        assert!(overlapping(region_tokens(), &(4..4)).is_empty());
+    }
+
+    #[test]
+    fn partition_by_span_and_first_at_match_a_linear_scan() {
+        let items = vec![
+            Spanned::new('a', 0..2),
+            Spanned::new('b', 2..3),
+            Spanned::new('c', 3..3),
+            Spanned::new('d', 5..8),
+            Spanned::new('e', 10..11),
+        ];
+        for pos in 0..13 {
+            let partition = items
+                .iter()
+                .position(|item| item.span().end > pos)
+                .unwrap_or(items.len());
+            assert_eq!(partition_by_span(&items, pos), partition, "at {}", pos);
+
+            let first = items.iter().find(|item| item.span().contains(&pos));
+            assert_eq!(first_at(&items, pos), first, "at {}", pos);
+        }
+    }
+
+    #[test]
+    fn keep_widest_keeps_disjoint_and_partially_overlapping_items() {
+        let items = vec![
+            Spanned::new('a', 4..8),
+            Spanned::new('b', 0..2),
+            Spanned::new('c', 6..10),
+        ];
+        assert_eq!(spans(&keep_widest(items)), [4..8, 0..2, 6..10]);
+    }
+
+    #[test]
+    fn zip3_covers_all_three_spans_in_any_order() {
+        let zipped = zip3(
+            Spanned::new('c', 8..9),
+            Spanned::new('a', 0..1),
+            Spanned::new('b', 4..5),
+        );
+        assert_eq!(zipped, Spanned::new(('c', 'a', 'b'), 0..9));
+    }
+
+    #[test]
+    fn zip_all_covers_every_span() {
+        let zipped = zip_all(vec![Spanned::new(1, 3..4), Spanned::new(2, 0..2)]);
+        assert_eq!(zipped, Spanned::new(vec![1, 2], 0..4));
+        assert_eq!(zip_all::<i32>(Vec::new()), Spanned::new(Vec::new(), 0..0));
+    }
+
+    #[test]
+    fn sort_by_span_is_stable_for_equal_spans() {
+        let mut items = vec![
+            Spanned::new('a', 4..6),
+            Spanned::new('b', 0..2),
+            Spanned::new('c', 4..6),
+            Spanned::new('d', 0..1),
+            Spanned::new('e', 4..6),
+        ];
+        sort_by_span(&mut items);
+        let order: String = items.iter().map(|item| item.inner).collect();
+        assert_eq!(order, "dbace");
+    }
+
+    #[test]
+    fn sort_by_span_desc_is_stable_for_equal_spans() {
+        let mut items = vec![
+            Spanned::new('a', 4..6),
+            Spanned::new('b', 0..2),
+            Spanned::new('c', 4..6),
+            Spanned::new('d', 0..1),
+        ];
+        sort_by_span_desc(&mut items);
+        let order: String = items.iter().map(|item| item.inner).collect();
+        assert_eq!(order, "acbd");
+    }
+
+    #[test]
+    fn lift_keeps_the_span_of_every_value() {
+        let items: Vec<Spanned<Result<i32, char>>> =
+            vec![Spanned::new(Ok(1), 0..1), Spanned::new(Ok(2), 3..5)];
+        assert_eq!(
+            lift(items),
+            Ok(vec![Spanned::new(1, 0..1), Spanned::new(2, 3..5)])
+        );
+    }
+
+    #[test]
+    fn lift_keeps_the_span_of_every_error() {
+        let items: Vec<Spanned<Result<i32, char>>> = vec![
+            Spanned::new(Err('x'), 0..1),
+            Spanned::new(Ok(2), 3..5),
+            Spanned::new(Err('y'), 6..7),
+        ];
+        assert_eq!(
+            lift(items),
+            Err(vec![Spanned::new('x', 0..1), Spanned::new('y', 6..7)])
+        );
+    }
+
+    #[test]
+    fn validate_all_reports_every_invalid_span() {
+        let source = "aé b";
+        let items = [
+            Spanned::new((), 0..1),
+            Spanned::new((), 2..3),
+            Spanned::new((), 1..3),
+            Spanned::new((), 3..9),
+            Spanned::new((), 5..5),
+        ];
+        assert_eq!(
+            validate_all(&items, source),
+            [
+                (
+                    1,
+                    SpanError::NotCharBoundary {
+                        span: 2..3,
+                        index: 2
+                    }
+                ),
+                (3, SpanError::OutOfBounds { span: 3..9, len: 5 }),
+            ]
+        );
+    }
+
+    #[test]
+    fn validate_all_of_valid_spans_is_empty() {
+        let items = [Spanned::new((), 0..1), Spanned::new((), 1..3)];
+        assert!(validate_all(&items, "aé").is_empty());
+    }
+
+    #[test]
+    fn absolutize_moves_every_span_to_the_origin() {
+        let template = "{{ name }}";
+        let source = format!("<p>{}</p>", template);
+        let origin = source.find(template).unwrap();
+        let relative = vec![Spanned::new("open", 0..2), Spanned::new("name", 3..7)];
+        let absolute = absolutize(relative, origin);
+        assert_eq!(spans(&absolute), [3..5, 6..10]);
+        assert_eq!(&source[absolute[1].span()], "name");
+    }
+
+    #[test]
+    fn index_by_start_keeps_the_last_of_equal_starts() {
+        let index = index_by_start(vec![
+            Spanned::new('a', 4..6),
+            Spanned::new('b', 0..2),
+            Spanned::new('c', 4..5),
+        ]);
+        assert_eq!(index.keys().copied().collect::<Vec<_>>(), [0, 4]);
+        assert_eq!(index[&4], Spanned::new('c', 4..5));
+    }
+
+    #[test]
+    fn range_query_yields_items_starting_in_the_region() {
+        let index = index_by_start(vec![
+            Spanned::new('a', 0..2),
+            Spanned::new('b', 3..9),
+            Spanned::new('c', 5..6),
+            Spanned::new('d', 8..9),
+        ]);
+        let found: String = range_query(&index, &(2..8))
+            .map(|item| item.inner)
+            .collect();
+        assert_eq!(found, "bc");
+        assert_eq!(range_query(&index, &(5..5)).count(), 0);
+        #[allow(clippy::reversed_empty_ranges)]
+        let inverted = 8..2;
+        assert_eq!(range_query(&index, &inverted).count(), 0);
+    }
+
+    #[test]
+    fn shortest_and_longest_pick_among_overlapping_candidates() {
+        let candidates = [
+            Spanned::new('a', 2..8),
+            Spanned::new('b', 3..5),
+            Spanned::new('c', 0..9),
+            Spanned::new('d', 4..6),
+        ];
+        assert_eq!(shortest(&candidates).map(|item| item.inner), Some('b'));
+        assert_eq!(longest(&candidates).map(|item| item.inner), Some('c'));
+    }
+
+    #[test]
+    fn shortest_and_longest_break_ties_by_start_then_order() {
+        let candidates = [
+            Spanned::new('a', 4..6),
+            Spanned::new('b', 1..3),
+            Spanned::new('c', 1..3),
+        ];
+        assert_eq!(shortest(&candidates).map(|item| item.inner), Some('b'));
+        assert_eq!(longest(&candidates).map(|item| item.inner), Some('b'));
+        assert!(shortest::<()>(&[]).is_none());
+        assert!(longest::<()>(&[]).is_none());
+    }
+
+    #[test]
+    fn merge_sorted_interleaves_two_streams() {
+        let code = vec![Spanned::new("let", 0..3), Spanned::new(";", 20..21)];
+        let strings = vec![Spanned::new("\"a\"", 8..11), Spanned::new("\"b\"", 14..17)];
+        assert_eq!(
+            merge_sorted(code, strings),
+            [
+                Spanned::new("let", 0..3),
+                Spanned::new("\"a\"", 8..11),
+                Spanned::new("\"b\"", 14..17),
+                Spanned::new(";", 20..21),
+            ]
+        );
+    }
+
+    #[test]
+    fn merge_sorted_puts_a_first_on_tied_starts() {
+        let a = vec![Spanned::new('a', 0..1), Spanned::new('b', 2..4)];
+        let b = vec![Spanned::new('x', 0..2), Spanned::new('y', 2..3)];
+        let merged: Vec<char> = merge_sorted(a, b)
+            .into_iter()
+            .map(Spanned::unwrap)
+            .collect();
+        assert_eq!(merged, ['a', 'x', 'b', 'y']);
+        assert_eq!(
+            merge_sorted(Vec::new(), vec![Spanned::new((), 1..2)]),
+            [Spanned::new((), 1..2)]
+        );
+    }
+}
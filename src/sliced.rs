@@ -1,12 +1,22 @@
 use std::{
-    fmt::Display,
-    ops::{Deref, DerefMut},
+    borrow::Cow,
+    cmp::Ordering,
+    fmt::{Debug, Display},
+    iter::{once, Once},
+    ops::{Deref, DerefMut, Range},
+    sync::Arc,
 };
 
-use crate::Span;
+use crate::{
+    edit,
+    span::{self, apply_edit, checked_shift, contains, line_col, line_spans},
+    span_union, HasSpan, Span, SpanError, Spanned,
+};
+
+/// The maximum number of `char`s of the slice shown by the `Debug` implementation of `Sliced`.
+const DEBUG_SLICE_LEN: usize = 32;
 
 /// A value of type `T` associated with a slice of the source text.
-#[derive(Debug)]
 pub struct Sliced<'source, T> {
     pub inner: T,
     span: Span,
@@ -20,12 +30,25 @@ impl<'source, T> Sliced<'source, T> {
     /// - `inner`: The value to be wrapped.
     /// - `span`: The range in the source text that corresponds to the value.
     /// - `source`: The source text that the wrapped value corresponds to a slice of.
+    ///
+    /// # Panics
+    /// In debug builds, panics if `span` is not valid for `source`, see [`Sliced::validate`].
+    #[track_caller]
     pub fn new(inner: T, span: Span, source: &'source str) -> Self {
-        Self {
+        let sliced = Self {
             inner,
             span,
             source,
-        }
+        };
+        sliced.debug_assert_valid();
+        sliced
+    }
+
+    /// Creates a new `Sliced` value associated with the same span and source as `other`.
+    ///
+    /// Useful for attributing a computed value to the location of the value it came from.
+    pub fn borrow_span_from<U>(inner: T, other: &Sliced<'source, U>) -> Self {
+        Self::new(inner, other.span(), other.source)
     }
 
     /// Consume `self` and return the inner, wrapped value
@@ -33,12 +56,286 @@ impl<'source, T> Sliced<'source, T> {
         self.inner
     }
 
+    /// Returns an iterator yielding `self` exactly once.
+    ///
+    /// Useful for chaining a single value onto an iterator of `Sliced` values.
+    pub fn once(self) -> Once<Sliced<'source, T>> {
+        once(self)
+    }
+
+    /// Maps a `&Sliced<T>` to a `Sliced<U>` by applying `f` to a reference to the wrapped value.
+    ///
+    /// `self` is not consumed, so this is useful for deriving a new value without cloning.
+    pub fn map_ref<U>(&self, f: impl FnOnce(&T) -> U) -> Sliced<'source, U> {
+        Sliced::new(f(&self.inner), self.span.clone(), self.source)
+    }
+
+    /// Borrows a part of the wrapped value, such as a field, keeping the span.
+    ///
+    /// Unlike [`Sliced::map_ref`], the result borrows from `self`, so the part needn't be cloned.
+    pub fn project<U>(&self, f: impl FnOnce(&T) -> &U) -> Sliced<'source, &U> {
+        Sliced::new(f(&self.inner), self.span.clone(), self.source)
+    }
+
+    /// Maps a `Sliced<T>` to a `Sliced<U>` by applying `f` to the wrapped value, the span, and
+    /// the slice.
+    ///
+    /// The result keeps the original span and source.
+    pub fn map_with_span<U>(
+        self,
+        f: impl FnOnce(T, Span, &'source str) -> U,
+    ) -> Sliced<'source, U> {
+        let slice = self.slice();
+        let span = self.span;
+        Sliced::new(f(self.inner, span.clone(), slice), span, self.source)
+    }
+
+    /// Replaces the wrapped value with `new`, which may be of a different type, keeping the span.
+    ///
+    /// This reads better than `map_with_span(|_, _, _| new)` when reclassifying a value, such as
+    /// turning a keyword token into an identifier.
+    pub fn retag<U>(self, new: U) -> Sliced<'source, U> {
+        Sliced::new(new, self.span, self.source)
+    }
+
+    /// Renders the wrapped value with [`ToString::to_string`], keeping the span.
+    ///
+    /// This is the same as `map_ref(ToString::to_string)`.
+    pub fn display_to_spanned(&self) -> Sliced<'source, String>
+    where
+        T: Display,
+    {
+        self.map_ref(ToString::to_string)
+    }
+
+    /// Consume `self` and return the wrapped value, its span, and the source string.
+    pub fn into_parts(self) -> (T, Span, &'source str) {
+        (self.inner, self.span, self.source)
+    }
+
+    /// Borrows the wrapped value, its span, and the source string.
+    ///
+    /// This is the borrowing equivalent of [`Sliced::into_parts`], and doesn't clone the span.
+    pub fn as_tuple(&self) -> (&T, &Span, &'source str) {
+        (&self.inner, &self.span, self.source)
+    }
+
     /// The span associated with the wrapped value.
     pub fn span(&self) -> Span {
         self.span.clone()
     }
 
+    /// The span associated with the wrapped value, as a plain `Range<usize>`.
+    ///
+    /// This is a synonym of [`Sliced::span`].
+    pub fn range(&self) -> Range<usize> {
+        self.span()
+    }
+
+    /// Replaces the span associated with the wrapped value.
+    ///
+    /// # Panics
+    /// In debug builds, panics if `span` is not valid for the source, see [`Sliced::validate`].
+    #[track_caller]
+    pub fn set_span(&mut self, span: Span) {
+        self.span = span;
+        self.debug_assert_valid();
+    }
+
+    /// Moves the span by `delta` bytes, keeping its length.
+    ///
+    /// # Panics
+    /// Panics if either end of the span overflows or underflows, or if the moved span is not
+    /// valid for the source, see [`Sliced::checked_shift`].
+    pub fn shift(self, delta: isize) -> Self {
+        self.checked_shift(delta)
+            .expect("span shift overflowed, underflowed, or left the source")
+    }
+
+    /// Moves the span by `delta` bytes, keeping its length.
+    ///
+    /// Returns `None` if either end of the span overflows or underflows, or if the moved span is
+    /// not valid for the source, see [`Sliced::validate`].
+    pub fn checked_shift(self, delta: isize) -> Option<Self> {
+        let span = checked_shift(&self.span, delta)?;
+        span::validate(&span, self.source).ok()?;
+        Some(Self::new(self.inner, span, self.source))
+    }
+
+    /// Returns a copy of the source with the slice replaced by `replacement`.
+    ///
+    /// # Panics
+    /// Panics if the span is not valid for the source, see [`Sliced::validate`].
+    pub fn replace_in(&self, replacement: &str) -> String {
+        edit::replace(self.source, &self.span, replacement)
+    }
+
+    /// Splits the span into the sub-span it covers on each line of the source.
+    ///
+    /// A span within a single line produces a single sub-span. Newlines are excluded from the
+    /// sub-spans, and a span ending just after a newline doesn't produce an empty sub-span for
+    /// the following line.
+    pub fn line_spans(&self) -> Vec<Span> {
+        line_spans(&self.span, self.source)
+    }
+
+    /// The span extended to the start of its first line and the end of its last line of the
+    /// source, excluding the final newline.
+    ///
+    /// A non-empty span ending just after a newline isn't extended over the following line.
+    pub fn widen_to_lines(&self) -> Span {
+        span::widen_to_lines(&self.span, self.source)
+    }
+
+    /// The span shrunk to exclude the whitespace at either end of the slice.
+    ///
+    /// A slice of only whitespace shrinks to an empty span at its end.
+    pub fn narrow_to_trimmed(&self) -> Span {
+        span::narrow_to_trimmed(&self.span, self.source)
+    }
+
+    /// Grows the span to also cover the span of `child`.
+    ///
+    /// # Panics
+    /// In debug builds, panics if the grown span is not valid for the source, see
+    /// [`Sliced::validate`].
+    #[track_caller]
+    pub fn extend_to_cover(&mut self, child: &impl HasSpan) {
+        self.span = span_union(&[self.span.clone(), child.span()]);
+        self.debug_assert_valid();
+    }
+
+    /// The 1-based `(line, column)` positions of the start and end of the slice.
+    ///
+    /// Columns are counted in `char`s, as for [`Sliced::line_col`]. See
+    /// [`from_line_col_range`](crate::from_line_col_range) for the inverse.
+    pub fn to_line_col_range(&self) -> Range<(usize, usize)> {
+        line_col(self.source, self.span.start)..line_col(self.source, self.span.end)
+    }
+
+    /// Returns `true` if the span shares at least one position with `other`.
+    ///
+    /// Adjacent spans such as `1..3` and `3..5` don't overlap, and an empty span never overlaps
+    /// anything, even a span containing its position.
+    pub fn overlaps(&self, other: &Span) -> bool {
+        span::overlaps(&self.span, other)
+    }
+
+    /// Returns `true` if the span overlaps `other` or is adjacent to it, so that
+    /// `self.end == other.start` or `other.end == self.start`.
+    ///
+    /// Unlike [`overlaps`](Self::overlaps), an empty span touches any span which contains or
+    /// borders its position, so `3..3` touches `1..5`, `1..3`, `3..5` and `3..3`.
+    pub fn touches(&self, other: &Span) -> bool {
+        span::touches(&self.span, other)
+    }
+
+    /// Returns `true` if the span's start is greater than its end.
+    pub fn is_inverted(&self) -> bool {
+        self.span.start > self.span.end
+    }
+
+    /// Swaps the start and end of the span if it is inverted.
+    ///
+    /// This only reorders the ends of the span, it never clamps them to the bounds of a source.
+    ///
+    /// # Panics
+    /// In debug builds, panics if the reordered span is not valid for the source, see
+    /// [`Sliced::validate`].
+    #[track_caller]
+    pub fn normalize(mut self) -> Self {
+        if self.is_inverted() {
+            self.span = self.span.end..self.span.start;
+        }
+        self.debug_assert_valid();
+        self
+    }
+
+    /// Shortens the span to at most `max_len` bytes from its start.
+    ///
+    /// The new end of the span is moved back to the nearest `char` boundary, so a valid span
+    /// stays valid.
+    ///
+    /// # Panics
+    /// In debug builds, panics if the shortened span is not valid for the source, see
+    /// [`Sliced::validate`].
+    #[track_caller]
+    pub fn truncate(mut self, max_len: usize) -> Self {
+        let mut end = self.span.end.min(self.span.start.saturating_add(max_len));
+        while end > self.span.start && !self.source.is_char_boundary(end) {
+            end -= 1;
+        }
+        self.span.end = end;
+        self.debug_assert_valid();
+        self
+    }
+
+    /// Fixes up the span after an edit replaced `removed` bytes at `edit_start` with `inserted`
+    /// bytes, producing `new_source`.
+    ///
+    /// A span entirely before the edit is left untouched, and a span entirely after the edit is
+    /// moved to account for the change in length. On success, `self` becomes a slice of
+    /// `new_source`. Returns `false` if the span overlaps the replaced bytes, can't be moved
+    /// without overflowing, or isn't valid for `new_source` once moved, in which case `self` is
+    /// left untouched.
+    pub fn apply_edit(
+        &mut self,
+        new_source: &'source str,
+        edit_start: usize,
+        removed: usize,
+        inserted: usize,
+    ) -> bool {
+        match apply_edit(&self.span, edit_start, removed, inserted)
+            .filter(|span| span::validate(span, new_source).is_ok())
+        {
+            Some(span) => {
+                self.span = span;
+                self.source = new_source;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns a clone of `self` associated with `span` instead, leaving `self` untouched.
+    pub fn respanned(&self, span: Span) -> Sliced<'source, T>
+    where
+        T: Clone,
+    {
+        Sliced::new(self.inner.clone(), span, self.source)
+    }
+
+    /// The span of the slice relative to its own start, that is, `0..len`.
+    pub fn relative_span(&self) -> Span {
+        0..self.span.len()
+    }
+
+    /// Translates `absolute`, a span in the source, into a span relative to the start of the
+    /// slice.
+    ///
+    /// Returns `None` if `absolute` doesn't lie entirely within the slice.
+    pub fn to_relative(&self, absolute: &Span) -> Option<Span> {
+        if !contains(&self.span, absolute) {
+            return None;
+        }
+        Some(absolute.start - self.span.start..absolute.end - self.span.start)
+    }
+
+    /// Translates `relative`, a span relative to the start of the slice, into a span in the
+    /// source. This is the inverse of [`Sliced::to_relative`].
+    ///
+    /// Returns `None` if `relative` is inverted, doesn't lie entirely within the slice, or
+    /// doesn't lie on `char` boundaries.
+    pub fn subspan(&self, relative: &Span) -> Option<Span> {
+        span::subspan(&self.span, relative).filter(|absolute| {
+            self.source.is_char_boundary(absolute.start)
+                && self.source.is_char_boundary(absolute.end)
+        })
+    }
+
     /// The slice associated with the wrapped value.
+    ///
+    /// An empty span gives `""`, including a `0..0` span over an empty source.
     pub fn slice(&self) -> &'source str {
         &self.source[self.span.clone()]
     }
@@ -47,6 +344,160 @@ impl<'source, T> Sliced<'source, T> {
     pub fn source(&self) -> &'source str {
         self.source
     }
+
+    /// Checks that the span is well-ordered, within the source, and lies on `char` boundaries.
+    ///
+    /// [`Sliced::slice`] does not panic for a span which passes this check.
+    pub fn validate(&self) -> Result<(), SpanError> {
+        span::validate(&self.span, self.source)
+    }
+
+    /// Panics with a detailed message if the span is not valid for the source, see
+    /// [`Sliced::validate`].
+    ///
+    /// This is intended for use in tests and debug builds.
+    #[track_caller]
+    pub fn debug_validate(&self) {
+        if let Err(error) = self.validate() {
+            panic!(
+                "invalid span for `Sliced` over a source of {} bytes: {}",
+                self.source.len(),
+                error
+            );
+        }
+    }
+
+    /// Calls [`Sliced::debug_validate`] in debug builds only.
+    #[track_caller]
+    fn debug_assert_valid(&self) {
+        if cfg!(debug_assertions) {
+            self.debug_validate();
+        }
+    }
+
+    /// The source text before the slice.
+    ///
+    /// Returns `""` if the span doesn't start on a `char` boundary within the source.
+    pub fn before(&self) -> &'source str {
+        self.source.get(..self.span.start).unwrap_or("")
+    }
+
+    /// The source text after the slice.
+    ///
+    /// Returns `""` if the span doesn't end on a `char` boundary within the source.
+    pub fn after(&self) -> &'source str {
+        self.source.get(self.span.end..).unwrap_or("")
+    }
+
+    /// The number of `char`s in the slice.
+    pub fn char_len(&self) -> usize {
+        self.slice().chars().count()
+    }
+
+    /// Replaces the source with the sub-slice `window` of it, re-basing the span to be relative
+    /// to the start of `window`.
+    ///
+    /// Useful for keeping only an excerpt of a large source alongside a value. Returns `None` if
+    /// the span doesn't lie entirely within `window`, or if `window` is not valid for the source.
+    pub fn reslice_source(self, window: Span) -> Option<Sliced<'source, T>> {
+        if !contains(&window, &self.span) {
+            return None;
+        }
+        let source = self.source.get(window.clone())?;
+        let span = self.span.start - window.start..self.span.end - window.start;
+        Some(Sliced::new(self.inner, span, source))
+    }
+
+    /// Returns `true` if `self` and `other` are associated with the very same source string.
+    ///
+    /// This compares the identity of the sources, not just their contents.
+    pub fn same_source<U>(&self, other: &Sliced<'_, U>) -> bool {
+        std::ptr::eq(self.source, other.source)
+    }
+
+    /// Combines `self` and `other` into one `Sliced` value covering both of their spans.
+    ///
+    /// Both must be associated with the same source string, see [`Sliced::same_source`].
+    /// Otherwise, `self` and `other` are returned unchanged as an error.
+    #[allow(clippy::type_complexity)]
+    pub fn merge_with<U>(
+        self,
+        other: Sliced<'source, U>,
+    ) -> Result<Sliced<'source, (T, U)>, (Self, Sliced<'source, U>)> {
+        if !self.same_source(&other) {
+            return Err((self, other));
+        }
+        let span = span_union(&[self.span, other.span]);
+        Ok(Sliced::new((self.inner, other.inner), span, self.source))
+    }
+
+    /// The length of the source string in bytes.
+    pub fn source_len(&self) -> usize {
+        self.source.len()
+    }
+
+    /// The number of bytes in the source before the slice.
+    pub fn bytes_before(&self) -> usize {
+        self.span.start
+    }
+
+    /// The number of bytes in the source after the slice.
+    ///
+    /// Returns `0` if the span ends past the end of the source.
+    pub fn bytes_after(&self) -> usize {
+        self.source.len().saturating_sub(self.span.end)
+    }
+
+    /// Returns a borrowed view of `self` with the source lifetime shortened to that of the borrow.
+    ///
+    /// Since `self` is borrowed for `'short`, `'source` must outlive `'short`, so the source can
+    /// always be viewed with the shorter lifetime. This is handy when passing a `Sliced` to
+    /// functions which tie the source and value lifetimes together.
+    pub fn reborrow<'short>(&'short self) -> Sliced<'short, &'short T> {
+        Sliced::new(&self.inner, self.span.clone(), self.source)
+    }
+
+    /// The 1-based line and column at which the slice starts.
+    ///
+    /// Columns are counted in `char`s. A slice at the start of the source, such as a `0..0` span
+    /// over an empty source, is at `(1, 1)`.
+    pub fn line_col(&self) -> (usize, usize) {
+        line_col(self.source, self.span.start)
+    }
+
+    /// Returns an adapter which displays the wrapped value followed by its `line:col` position.
+    pub fn located(&self) -> impl Display + '_
+    where
+        T: Display,
+    {
+        Located(self)
+    }
+
+    /// Compares the lengths of the spans of `self` and `other`, breaking ties by the start of
+    /// the span.
+    ///
+    /// The wrapped values are ignored. An inverted span has a length of `0`.
+    pub fn cmp_by_len(&self, other: &Self) -> Ordering {
+        span::cmp_by_len(&self.span, &other.span)
+    }
+
+    /// Returns `true` if the wrapped value is equal to `other`, ignoring the span and source.
+    pub fn eq_value(&self, other: &T) -> bool
+    where
+        T: PartialEq,
+    {
+        self.inner == *other
+    }
+
+    /// Returns `true` if the slice associated with the wrapped value is equal to `text`.
+    pub fn eq_slice(&self, text: &str) -> bool {
+        self.slice() == text
+    }
+
+    /// Maps a `Sliced<T>` to a `Sliced<Arc<T>>` so the wrapped value can be shared, e.g. across threads.
+    pub fn shared(self) -> Sliced<'source, Arc<T>> {
+        Sliced::new(Arc::new(self.inner), self.span, self.source)
+    }
 }
 
 impl<'source, T, E> Sliced<'source, Result<T, E>> {
@@ -61,6 +512,49 @@ impl<'source, T, E> Sliced<'source, Result<T, E>> {
             Err(e) => Err(Sliced::new(e, self.span, self.source)),
         }
     }
+
+    /// Maps a `Sliced<Result<T, E>>` to a `Sliced<T>`, replacing an error with a recovery value.
+    ///
+    /// `Ok(t)` is passed through unchanged, while `Err(e)` is replaced by `f(e)`. The span and
+    /// source are kept in both cases.
+    pub fn recover(self, f: impl FnOnce(E) -> T) -> Sliced<'source, T> {
+        Sliced::new(self.inner.unwrap_or_else(f), self.span, self.source)
+    }
+
+    /// Maps a `Sliced<Result<T, E>>` to a `Sliced<T>`, replacing an error with `T::default()`.
+    ///
+    /// This is the same as [`Sliced::recover`], but discards the error.
+    pub fn or_default(self) -> Sliced<'source, T>
+    where
+        T: Default,
+    {
+        self.recover(|_| T::default())
+    }
+
+    /// Maps both arms of a `Sliced<Result<T, E>>`, applying `ok` to a value or `err` to an error.
+    pub fn map_both<U, F>(
+        self,
+        ok: impl FnOnce(T) -> U,
+        err: impl FnOnce(E) -> F,
+    ) -> Sliced<'source, Result<U, F>> {
+        let inner = match self.inner {
+            Ok(t) => Ok(ok(t)),
+            Err(e) => Err(err(e)),
+        };
+        Sliced::new(inner, self.span, self.source)
+    }
+
+    /// Maps the error of a `Sliced<Result<T, E>>`, passing the span to `f` alongside it.
+    ///
+    /// This allows the span to be embedded into error types which carry their own location.
+    pub fn map_err_span(self, f: impl FnOnce(E, Span) -> E) -> Self {
+        let span = self.span;
+        Sliced::new(
+            self.inner.map_err(|e| f(e, span.clone())),
+            span,
+            self.source,
+        )
+    }
 }
 
 impl<'source, T> Sliced<'source, Option<T>> {
@@ -74,6 +568,113 @@ impl<'source, T> Sliced<'source, Option<T>> {
             None => None,
         }
     }
+
+    /// Maps the `Some` value of a `Sliced<Option<T>>` by applying `f` to it.
+    ///
+    /// Unlike `unzip`, the span is kept whether the value is `Some` or `None`.
+    pub fn map_opt<U>(self, f: impl FnOnce(T) -> U) -> Sliced<'source, Option<U>> {
+        Sliced::new(self.inner.map(f), self.span, self.source)
+    }
+}
+
+impl<K, T> Sliced<'_, (K, T)> {
+    /// The first element of the wrapped pair, such as the kind of a token.
+    pub fn first(&self) -> &K {
+        &self.inner.0
+    }
+
+    /// The second element of the wrapped pair, such as the value of a token.
+    pub fn second(&self) -> &T {
+        &self.inner.1
+    }
+}
+
+impl<'source> Sliced<'source, &'source str> {
+    /// Finds the first occurrence of `needle` in `source`, returning it as a `Sliced` value.
+    pub fn locate(source: &'source str, needle: &str) -> Option<Self> {
+        Self::locate_all(source, needle).next()
+    }
+
+    /// Finds every non-overlapping occurrence of `needle` in `source`, returning them as
+    /// `Sliced` values.
+    pub fn locate_all<'a>(source: &'source str, needle: &'a str) -> impl Iterator<Item = Self> + 'a
+    where
+        'source: 'a,
+    {
+        source
+            .match_indices(needle)
+            .map(move |(start, text)| Sliced::new(text, start..start + text.len(), source))
+    }
+
+    /// Splits off the first `at` bytes of the slice, returning them as a new `Sliced` value and
+    /// advancing `self` to cover the remainder.
+    ///
+    /// Unlike [`String::split_off`], the front of the slice is returned. Both the returned value
+    /// and `self` wrap their slice of the source, and their spans are contiguous.
+    ///
+    /// # Panics
+    /// Panics if `at` is past the end of the slice or is not on a `char` boundary. In debug
+    /// builds, also panics if either part is not valid for the source, see [`Sliced::validate`].
+    #[track_caller]
+    pub fn split_off(&mut self, at: usize) -> Sliced<'source, &'source str> {
+        let (front, rest) = self.slice().split_at(at);
+        let mid = self.span.start + at;
+        let front = Sliced::new(front, self.span.start..mid, self.source);
+        self.inner = rest;
+        self.span.start = mid;
+        self.debug_assert_valid();
+        front
+    }
+
+    /// Splits the slice by whitespace, returning each word as a `Sliced` value with its span in
+    /// the source.
+    ///
+    /// Like [`str::split_whitespace`], runs of whitespace are skipped and no empty words are
+    /// returned.
+    pub fn split_whitespace(self) -> impl Iterator<Item = Sliced<'source, &'source str>> {
+        let slice = self.slice();
+        let start = self.span.start;
+        let source = self.source;
+        slice.split_whitespace().map(move |word| {
+            let offset = start + (word.as_ptr() as usize - slice.as_ptr() as usize);
+            Sliced::new(word, offset..offset + word.len(), source)
+        })
+    }
+
+    /// Maps the slice to text which may either borrow from the source or be newly allocated,
+    /// keeping the span.
+    ///
+    /// This suits transforms like escape processing, where most slices are returned unchanged
+    /// as [`Cow::Borrowed`] without allocating.
+    pub fn mapped_text(
+        self,
+        f: impl FnOnce(&'source str) -> Cow<'source, str>,
+    ) -> Sliced<'source, Cow<'source, str>> {
+        Sliced::new(f(self.slice()), self.span, self.source)
+    }
+
+    /// Returns `true` if the wrapped string is `text`, such as when checking for a keyword.
+    ///
+    /// This compares the wrapped value rather than [`Sliced::slice`], though the two are the
+    /// same for a value created from its slice of the source.
+    pub fn is(&self, text: &str) -> bool {
+        self.inner == text
+    }
+
+    /// Returns the index of the first of `texts` which is the wrapped string, see
+    /// [`Sliced::is`].
+    pub fn is_any(&self, texts: &[&str]) -> Option<usize> {
+        texts.iter().position(|&text| self.is(text))
+    }
+
+    /// Returns the slice as a `Spanned<char>` if it consists of exactly one `char`.
+    pub fn single_char(&self) -> Option<Spanned<char>> {
+        let mut chars = self.slice().chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Some(Spanned::new(c, self.span())),
+            _ => None,
+        }
+    }
 }
 
 impl<'source, T> Sliced<'source, &T> {
@@ -128,6 +729,20 @@ impl<'source, T> Sliced<'source, &mut T> {
     }
 }
 
+impl<'source, T> Sliced<'source, Arc<T>> {
+    /// Maps a `Sliced<Arc<T>>` to a `Sliced<T>`, cloning the wrapped value if it is still shared.
+    pub fn unshared(self) -> Sliced<'source, T>
+    where
+        T: Clone,
+    {
+        Sliced::new(
+            Arc::try_unwrap(self.inner).unwrap_or_else(|arc| T::clone(&arc)),
+            self.span,
+            self.source,
+        )
+    }
+}
+
 impl<T> Clone for Sliced<'_, T>
 where
     T: Clone,
@@ -193,14 +808,736 @@ impl<'a, 'source, T> From<&'a mut Sliced<'source, T>> for Sliced<'source, &'a mu
     }
 }
 
+impl<T> From<&Sliced<'_, T>> for Span {
+    fn from(value: &Sliced<'_, T>) -> Self {
+        value.span()
+    }
+}
+
+impl<T> HasSpan for Sliced<'_, T> {
+    fn span(&self) -> Span {
+        self.span.clone()
+    }
+}
+
+/// Iterates over `self` as a single item, this does not iterate over the wrapped value.
+impl<'source, T> IntoIterator for Sliced<'source, T> {
+    type Item = Sliced<'source, T>;
+    type IntoIter = Once<Sliced<'source, T>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        once(self)
+    }
+}
+
 impl<T: PartialEq> PartialEq for Sliced<'_, T> {
     fn eq(&self, other: &Self) -> bool {
         self.inner == other.inner && self.span == other.span && self.source == other.source
     }
 }
 
+/// Displays a `Sliced` value followed by its `line:col` position, see [`Sliced::located`].
+struct Located<'a, 'source, T>(&'a Sliced<'source, T>);
+
+impl<T: Display> Display for Located<'_, '_, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (line, col) = self.0.line_col();
+        write!(f, "{} ({}:{})", self.0.inner, line, col)
+    }
+}
+
+/// Formats the slice of a `Sliced`, truncated to [`DEBUG_SLICE_LEN`] `char`s.
+struct DebugSlice<'source>(Option<&'source str>);
+
+impl Debug for DebugSlice<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Some(slice) = self.0 else {
+            return f.write_str("<out of bounds>");
+        };
+        match slice.char_indices().nth(DEBUG_SLICE_LEN) {
+            Some((end, _)) => write!(f, "{:?}...", &slice[..end]),
+            None => write!(f, "{:?}", slice),
+        }
+    }
+}
+
+/// Unlike a derived implementation this does not print the whole source, only the (truncated)
+/// slice associated with the wrapped value.
+impl<T: Debug> Debug for Sliced<'_, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Sliced")
+            .field("inner", &self.inner)
+            .field("span", &self.span)
+            .field("slice", &DebugSlice(self.source.get(self.span.clone())))
+            .finish()
+    }
+}
+
 impl<T: Display> Display for Sliced<'_, T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}(\"{}\")", self.inner, self.slice())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn eq_value_compares_only_the_inner_value() {
+        let sliced = Sliced::new(4, 0..3, "let x");
+        assert!(sliced.eq_value(&4));
+        assert!(!sliced.eq_value(&5));
+    }
+
+    #[test]
+    fn eq_slice_compares_the_slice() {
+        let sliced = Sliced::new((), 0..3, "let x");
+        assert!(sliced.eq_slice("let"));
+        assert!(!sliced.eq_slice("le"));
+        assert!(!sliced.eq_slice("let x"));
+    }
+
+    #[test]
+    fn into_parts_round_trips_through_new() {
+        let sliced = Sliced::new(7, 4..5, "let x");
+        let (inner, span, source) = sliced.clone().into_parts();
+        assert_eq!((inner, span.clone(), source), (7, 4..5, "let x"));
+        assert_eq!(Sliced::new(inner, span, source), sliced);
+    }
+
+    #[test]
+    fn single_char_extracts_exactly_one_char() {
+        let source = "fé=ab";
+        let sliced = Sliced::new(&source[1..3], 1..3, source);
+        assert_eq!(sliced.single_char(), Some(Spanned::new('é', 1..3)));
+        let sliced = Sliced::new(&source[4..6], 4..6, source);
+        assert_eq!(sliced.single_char(), None);
+        let sliced = Sliced::new(&source[3..3], 3..3, source);
+        assert_eq!(sliced.single_char(), None);
+    }
+
+    #[test]
+    fn recover_keeps_the_span_of_both_arms() {
+        let ok: Sliced<Result<i32, &str>> = Sliced::new(Ok(1), 0..1, "x?");
+        assert_eq!(ok.recover(|_| 0), Sliced::new(1, 0..1, "x?"));
+        let err: Sliced<Result<i32, &str>> = Sliced::new(Err("bad"), 0..1, "x?");
+        assert_eq!(err.recover(|e| e.len() as i32), Sliced::new(3, 0..1, "x?"));
+    }
+
+    #[test]
+    fn debug_truncates_a_long_slice() {
+        let source = "x".repeat(1000);
+        let sliced = Sliced::new(1, 0..1000, &source);
+        assert_eq!(
+            format!("{:?}", sliced),
+            format!(
+                "Sliced {{ inner: 1, span: 0..1000, slice: {:?}... }}",
+                "x".repeat(32)
+            )
+        );
+    }
+
+    #[test]
+    fn debug_shows_a_short_slice_but_not_the_source() {
+        let source = format!("let{}", "-".repeat(1000));
+        let sliced = Sliced::new((), 0..3, &source);
+        assert_eq!(
+            format!("{:?}", sliced),
+            "Sliced { inner: (), span: 0..3, slice: \"let\" }"
+        );
+    }
+
+    #[test]
+    fn shared_values_can_be_sent_across_threads() {
+        let source = "let node";
+        let shared = Sliced::new(String::from("node"), 4..8, source).shared();
+        let len = std::thread::scope(|scope| {
+            scope
+                .spawn(|| shared.map_ref(|node| node.len()))
+                .join()
+                .unwrap()
+        });
+        assert_eq!(len, Sliced::new(4, 4..8, source));
+        assert_eq!(
+            shared.unshared(),
+            Sliced::new(String::from("node"), 4..8, source)
+        );
+    }
+
+    #[test]
+    fn respanned_leaves_the_original_untouched() {
+        let original = Sliced::new("x", 0..1, "x y");
+        let copy = original.respanned(2..3);
+        assert_eq!(original.slice(), "x");
+        assert_eq!(copy.slice(), "y");
+        assert_eq!(copy.inner, "x");
+    }
+
+    #[test]
+    fn located_shows_the_line_and_column() {
+        let source = "let a = 1;\nlet bé = 2;";
+        let sliced = Sliced::new("=", 19..20, source);
+        assert_eq!(sliced.slice(), "=");
+        assert_eq!(sliced.located().to_string(), "= (2:8)");
+    }
+
+    #[test]
+    fn checked_shift_returns_none_when_leaving_the_source() {
+        let sliced = Sliced::new((), 1..2, "abc");
+        assert_eq!(sliced.clone().checked_shift(1).unwrap().slice(), "c");
+        assert!(sliced.clone().checked_shift(2).is_none());
+        assert!(sliced.clone().checked_shift(-2).is_none());
+        assert!(sliced.checked_shift(isize::MAX).is_none());
+    }
+
+    #[test]
+    fn checked_shift_returns_none_off_a_char_boundary() {
+        let sliced = Sliced::new((), 0..1, "aé");
+        assert!(sliced.checked_shift(1).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "span shift overflowed, underflowed, or left the source")]
+    fn shift_panics_when_leaving_the_source() {
+        Sliced::new((), 2..3, "abc").shift(1);
+    }
+
+    #[test]
+    fn reborrow_passes_a_short_lived_view() {
+        fn describe<'a>(sliced: Sliced<'a, &'a String>) -> &'a str {
+            sliced.slice()
+        }
+
+        let source = "let x";
+        let sliced = Sliced::new(String::from("keyword"), 0..3, source);
+        let view = sliced.reborrow();
+        assert_eq!(view.inner, "keyword");
+        assert_eq!(view.span(), 0..3);
+        assert_eq!(describe(sliced.reborrow()), "let");
+    }
+
+    #[test]
+    fn map_err_span_passes_the_span_to_the_error() {
+        let result: Sliced<Result<(), Span>> = Sliced::new(Err(0..0), 1..2, "abc");
+        let result = result.map_err_span(|_, span| span);
+        assert_eq!(result.inner, Err(1..2));
+    }
+
+    #[test]
+    fn byte_counts_around_the_slice() {
+        let sliced = Sliced::new((), 4..5, "let x = 1;");
+        assert_eq!(sliced.source_len(), 10);
+        assert_eq!(sliced.bytes_before(), 4);
+        assert_eq!(sliced.bytes_after(), 5);
+    }
+
+    #[test]
+    fn bytes_after_saturates_past_the_end_of_the_source() {
+        // Built directly, as `Sliced::new` rejects the span in debug builds.
+        let sliced = Sliced {
+            inner: (),
+            span: 2..8,
+            source: "abc",
+        };
+        assert_eq!(sliced.bytes_after(), 0);
+    }
+
+    #[test]
+    fn validate_checks_against_the_source() {
+        assert_eq!(Sliced::new((), 1..3, "héllo").validate(), Ok(()));
+        let sliced = Sliced {
+            inner: (),
+            span: 1..2,
+            source: "héllo",
+        };
+        assert_eq!(
+            sliced.validate(),
+            Err(SpanError::NotCharBoundary {
+                span: 1..2,
+                index: 2
+            })
+        );
+        let sliced = Sliced {
+            inner: (),
+            span: 0..9,
+            source: "héllo",
+        };
+        assert_eq!(
+            sliced.validate(),
+            Err(SpanError::OutOfBounds { span: 0..9, len: 6 })
+        );
+    }
+
+    #[test]
+    fn converts_into_a_range_for_slicing() {
+        let bytes = Vec::from([10, 20, 30, 40, 50]);
+        let sliced = Sliced::new((), 2..5, "abcde");
+        assert_eq!(bytes[Range::from(&sliced)], [30, 40, 50]);
+        assert_eq!(sliced.range(), 2..5);
+    }
+
+    #[test]
+    fn apply_edit_slices_the_new_source() {
+        let old = "let x = 1; y";
+        let new = "let value = 1; y";
+        let mut before = Sliced::new((), 0..3, old);
+        assert!(before.apply_edit(new, 4, 1, 5));
+        assert_eq!((before.slice(), before.source()), ("let", new));
+
+        let mut after = Sliced::new((), 11..12, old);
+        assert!(after.apply_edit(new, 4, 1, 5));
+        assert_eq!((after.span(), after.slice()), (15..16, "y"));
+    }
+
+    #[test]
+    fn apply_edit_invalidates_a_slice_overlapping_the_edit() {
+        let old = "let x = 1;";
+        let new = "let value = 1;";
+        let mut sliced = Sliced::new((), 4..7, old);
+        assert!(!sliced.apply_edit(new, 4, 1, 5));
+        assert_eq!((sliced.span(), sliced.source()), (4..7, old));
+    }
+
+    #[test]
+    fn apply_edit_rejects_a_span_invalid_for_the_new_source() {
+        let mut sliced = Sliced::new((), 4..5, "let x");
+        assert!(!sliced.apply_edit("let", 0, 0, 3));
+        assert_eq!(sliced.source(), "let x");
+    }
+
+    #[test]
+    fn truncate_shortens_a_long_slice() {
+        let source = format!("\"{}\"", "a".repeat(200));
+        let sliced = Sliced::new("literal", 0..202, &source).truncate(16);
+        assert_eq!(sliced.span(), 0..16);
+        assert_eq!(sliced.inner, "literal");
+    }
+
+    #[test]
+    fn truncate_stays_on_a_char_boundary() {
+        let source = "ééééé";
+        let sliced = Sliced::new((), 2..10, source).truncate(5);
+        assert_eq!(sliced.span(), 2..6);
+        assert_eq!(sliced.slice(), "éé");
+        assert_eq!(Sliced::new((), 2..10, source).truncate(1).slice(), "");
+    }
+
+    #[test]
+    fn extend_to_cover_grows_across_children() {
+        let source = "{ a; b; }";
+        let mut node = Sliced::new("block", 0..1, source);
+        node.extend_to_cover(&Spanned::new((), 2..4));
+        node.extend_to_cover(&Sliced::new((), 8..9, source));
+        assert_eq!(node.slice(), source);
+    }
+
+    #[test]
+    fn line_spans_splits_the_slice_by_line() {
+        let sliced = Sliced::new((), 1..9, "ab\ncde\nfg");
+        assert_eq!(sliced.line_spans(), [1..2, 3..6, 7..9]);
+    }
+
+    #[test]
+    fn map_both_applies_the_matching_closure_and_keeps_the_slice() {
+        let ok: Sliced<Result<i32, &str>> = Sliced::new(Ok(2), 0..2, "12");
+        let ok = ok.map_both(|t| t * 10, |e| e.len());
+        assert_eq!((ok.inner, ok.slice()), (Ok(20), "12"));
+        let err: Sliced<Result<i32, &str>> = Sliced::new(Err("bad"), 0..2, "12");
+        let err = err.map_both(|t| t * 10, |e| e.len());
+        assert_eq!((err.inner, err.slice()), (Err(3), "12"));
+    }
+
+    #[test]
+    #[allow(clippy::reversed_empty_ranges)]
+    fn normalize_reorders_an_inverted_span() {
+        let sliced = Sliced {
+            inner: (),
+            span: 3..1,
+            source: "abcd",
+        };
+        assert!(sliced.is_inverted());
+        let sliced = sliced.normalize();
+        assert!(!sliced.is_inverted());
+        assert_eq!(sliced.slice(), "bc");
+    }
+
+    #[test]
+    fn chains_a_single_value_onto_a_stream() {
+        let source = "ab";
+        let tokens = vec![Sliced::new('a', 0..1, source)];
+        let chained: Vec<Sliced<char>> = tokens
+            .into_iter()
+            .chain(Sliced::new('b', 1..2, source))
+            .chain(Sliced::new('$', 2..2, source).once())
+            .collect();
+        assert_eq!(chained.len(), 3);
+        assert_eq!(chained[1].slice(), "b");
+    }
+
+    #[test]
+    fn before_slice_and_after_reconstruct_the_source() {
+        let source = "let x = 1;";
+        let sliced = Sliced::new((), 4..5, source);
+        assert_eq!(sliced.before(), "let ");
+        assert_eq!(sliced.after(), " = 1;");
+        assert_eq!(
+            [sliced.before(), sliced.slice(), sliced.after()].concat(),
+            source
+        );
+    }
+
+    #[test]
+    fn before_and_after_are_empty_for_an_out_of_range_span() {
+        let sliced = Sliced {
+            inner: (),
+            span: 5..9,
+            source: "abc",
+        };
+        assert_eq!(sliced.before(), "");
+        assert_eq!(sliced.after(), "");
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "invalid span for `Sliced` over a source of 3 bytes: span 2..5 is out of bounds of a source of length 3"
+    )]
+    fn debug_validate_reports_an_out_of_bounds_span() {
+        let sliced = Sliced {
+            inner: (),
+            span: 2..5,
+            source: "abc",
+        };
+        sliced.debug_validate();
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(
+        expected = "invalid span for `Sliced` over a source of 2 bytes: span 0..1 does not lie on a char boundary at 1"
+    )]
+    fn new_panics_on_a_span_inside_a_code_point() {
+        Sliced::new((), 0..1, "é");
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "invalid span for `Sliced` over a source of 3 bytes: span 1..4")]
+    fn set_span_panics_on_an_invalid_span() {
+        Sliced::new((), 0..1, "abc").set_span(1..4);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(
+        expected = "invalid span for `Sliced` over a source of 3 bytes: span 1..3 does not lie on a char boundary at 1"
+    )]
+    #[allow(clippy::reversed_empty_ranges)]
+    fn normalize_panics_if_the_reordered_span_is_invalid() {
+        let sliced = Sliced {
+            inner: (),
+            span: 3..1,
+            source: "éa",
+        };
+        sliced.normalize();
+    }
+
+    #[test]
+    fn map_ref_derives_a_value_without_consuming() {
+        let name = Sliced::new(String::from("ident"), 0..5, "ident");
+        let len: Sliced<usize> = name.map_ref(String::len);
+        assert_eq!((len.inner, len.slice()), (5, "ident"));
+        assert_eq!(name.inner, "ident");
+    }
+
+    #[test]
+    fn replace_in_swaps_the_text_of_the_slice() {
+        let sliced = Sliced::new((), 4..5, "let x = 1;");
+        assert_eq!(sliced.replace_in("value"), "let value = 1;");
+    }
+
+    #[test]
+    fn char_len_counts_chars_rather_than_bytes() {
+        let source = "a名e\u{301}";
+        let sliced = Sliced::new((), 0..source.len(), source);
+        assert_eq!(sliced.span().len(), 7);
+        assert_eq!(sliced.char_len(), 4);
+    }
+
+    #[test]
+    fn merge_with_joins_slices_of_the_same_source() {
+        let source = "let x = 1;";
+        let keyword = Sliced::new("let", 0..3, source);
+        let name = Sliced::new('x', 4..5, source);
+        assert!(keyword.same_source(&name));
+        let merged = keyword.merge_with(name).unwrap();
+        assert_eq!(merged.inner, ("let", 'x'));
+        assert_eq!(merged.slice(), "let x");
+    }
+
+    #[test]
+    fn merge_with_rejects_an_equal_but_different_source() {
+        let source = "let x = 1;";
+        let copy = String::from(source);
+        let keyword = Sliced::new("let", 0..3, source);
+        let name = Sliced::new('x', 4..5, copy.as_str());
+        assert!(!keyword.same_source(&name));
+        let (keyword, name) = keyword.merge_with(name).unwrap_err();
+        assert_eq!((keyword.slice(), name.slice()), ("let", "x"));
+    }
+
+    #[test]
+    fn split_off_repeatedly_keeps_spans_contiguous() {
+        let source = "(let 名)";
+        let mut rest = Sliced::new(&source[1..], 1..9, source);
+        let keyword = rest.split_off(3);
+        let space = rest.split_off(1);
+        let name = rest.split_off(3);
+        assert_eq!(
+            [keyword.inner, space.inner, name.inner, rest.inner],
+            ["let", " ", "名", ")"]
+        );
+        assert_eq!(keyword.span(), 1..4);
+        assert_eq!(space.span(), 4..5);
+        assert_eq!(name.span(), 5..8);
+        assert_eq!(rest.span(), 8..9);
+        assert_eq!(rest.slice(), rest.inner);
+    }
+
+    #[test]
+    #[should_panic(expected = "not a char boundary")]
+    fn split_off_panics_off_a_char_boundary() {
+        let source = "名";
+        Sliced::new(source, 0..3, source).split_off(1);
+    }
+
+    #[test]
+    fn borrow_span_from_adopts_the_span_and_source_of_another_value() {
+        let token = Sliced::new("42", 4..6, "x = 42");
+        let value = Sliced::borrow_span_from(42, &token);
+        assert_eq!((value.inner, value.slice()), (42, "42"));
+    }
+
+    #[test]
+    fn to_relative_translates_into_slice_coordinates() {
+        let source = "fn main() {}";
+        let sliced = Sliced::new((), 3..9, source);
+        assert_eq!(sliced.relative_span(), 0..6);
+        assert_eq!(sliced.to_relative(&(7..9)), Some(4..6));
+        assert_eq!(sliced.to_relative(&(2..5)), None);
+        assert_eq!(sliced.to_relative(&(8..10)), None);
+    }
+
+    #[test]
+    fn to_relative_and_subspan_round_trip() {
+        let source = "fn main() {}";
+        let sliced = Sliced::new((), 3..9, source);
+        let relative = sliced.to_relative(&(3..7)).unwrap();
+        assert_eq!(sliced.subspan(&relative), Some(3..7));
+        assert_eq!(sliced.subspan(&sliced.relative_span()), Some(sliced.span()));
+    }
+
+    #[test]
+    fn reslice_source_rebases_onto_the_window() {
+        let source = "fn a() {}\nfn b() { oops }\nfn c() {}";
+        let sliced = Sliced::new("oops", 19..23, source);
+        let excerpt = sliced.reslice_source(10..25).unwrap();
+        assert_eq!(excerpt.source(), "fn b() { oops }");
+        assert_eq!(excerpt.span(), 9..13);
+        assert_eq!(excerpt.slice(), "oops");
+    }
+
+    #[test]
+    fn reslice_source_rejects_a_window_not_covering_the_span() {
+        let source = "fn a() {}\nfn b() { oops }";
+        let sliced = Sliced::new("oops", 19..23, source);
+        assert!(sliced.clone().reslice_source(0..21).is_none());
+        assert!(sliced.clone().reslice_source(20..25).is_none());
+        assert!(sliced.reslice_source(19..40).is_none());
+    }
+
+    #[test]
+    fn map_opt_keeps_the_slice_of_some_and_none() {
+        let some = Sliced::new(Some(2), 0..1, "2;").map_opt(|n| n * 2);
+        assert_eq!((some.inner, some.slice()), (Some(4), "2"));
+        let none: Sliced<Option<i32>> = Sliced::new(None, 1..2, "2;");
+        let none = none.map_opt(|n| n * 2);
+        assert_eq!((none.inner, none.slice()), (None, ";"));
+    }
+
+    #[test]
+    fn locate_all_finds_each_occurrence() {
+        let source = "x + x * x";
+        let spans: Vec<Span> = Sliced::locate_all(source, "x")
+            .map(|sliced| sliced.span())
+            .collect();
+        assert_eq!(spans, [0..1, 4..5, 8..9]);
+        assert!(Sliced::locate_all(source, "x").all(|sliced| sliced.slice() == "x"));
+    }
+
+    #[test]
+    fn locate_finds_the_first_occurrence() {
+        let source = "let a = b; let c = d;";
+        let sliced = Sliced::locate(source, "let").unwrap();
+        assert_eq!((sliced.inner, sliced.span()), ("let", 0..3));
+        assert_eq!(Sliced::locate(source, "c =").unwrap().span(), 15..18);
+        assert!(Sliced::locate(source, "fn").is_none());
+    }
+
+    #[test]
+    fn map_with_span_passes_the_span_and_slice_to_the_closure() {
+        let sliced = Sliced::new((), 4..6, "let xy");
+        let mapped = sliced.map_with_span(|_, span, slice| (span.start, slice.len()));
+        assert_eq!((mapped.inner, mapped.span()), ((4, 2), 4..6));
+    }
+
+    #[test]
+    fn or_default_substitutes_the_default_for_an_error() {
+        let ok: Sliced<Result<i32, &str>> = Sliced::new(Ok(5), 0..1, "5?");
+        assert_eq!(ok.or_default(), Sliced::new(5, 0..1, "5?"));
+        let err: Sliced<Result<i32, &str>> = Sliced::new(Err("bad"), 1..2, "5?");
+        assert_eq!(err.or_default(), Sliced::new(0, 1..2, "5?"));
+    }
+
+    #[test]
+    fn display_to_spanned_renders_the_value() {
+        let number = Sliced::new(-42, 4..7, "x = -42").display_to_spanned();
+        assert_eq!((number.inner.as_str(), number.slice()), ("-42", "-42"));
+    }
+
+    #[test]
+    fn split_whitespace_yields_each_word_with_its_span() {
+        let source = "(  foo  bar )";
+        let sliced = Sliced::new(&source[1..12], 1..12, source);
+        let words: Vec<(&str, Span)> = sliced
+            .split_whitespace()
+            .map(|word| (word.inner, word.span()))
+            .collect();
+        assert_eq!(words, [("foo", 3..6), ("bar", 8..11)]);
+    }
+
+    #[test]
+    fn split_whitespace_of_only_whitespace_is_empty() {
+        let source = " \t\n ";
+        let sliced = Sliced::new(source, 0..4, source);
+        assert_eq!(sliced.split_whitespace().count(), 0);
+    }
+
+    #[test]
+    fn overlaps_and_touches_compare_spans() {
+        let sliced = Sliced::new((), 1..3, "abcdef");
+        assert!(sliced.overlaps(&(2..4)));
+        assert!(!sliced.overlaps(&(3..4)));
+        assert!(sliced.touches(&(3..4)));
+        assert!(!sliced.touches(&(4..5)));
+        assert!(!Sliced::new((), 2..2, "abcdef").overlaps(&(1..3)));
+    }
+
+    #[test]
+    fn retag_keeps_the_slice() {
+        let keyword = Sliced::new('k', 4..8, "let type = 1;");
+        let ident = keyword.retag("ident");
+        assert_eq!((ident.inner, ident.slice()), ("ident", "type"));
+    }
+
+    #[test]
+    fn project_borrows_a_field_with_the_parent_slice() {
+        let pair = Sliced::new((1, "b"), 0..5, "(1,b)");
+        let second = pair.project(|pair| &pair.1);
+        assert_eq!((*second.inner, second.slice()), ("b", "(1,b)"));
+    }
+
+    #[test]
+    fn is_and_is_any_match_keywords() {
+        let token = Sliced::locate("if let x", "let").unwrap();
+        assert!(token.is("let"));
+        assert!(!token.is("le"));
+        assert_eq!(token.is_any(&["if", "let", "else"]), Some(1));
+        assert_eq!(token.is_any(&["if", "else"]), None);
+    }
+
+    #[test]
+    fn is_compares_the_wrapped_value_rather_than_the_slice() {
+        let token = Sliced::new("fn", 0..3, "let");
+        assert!(token.is("fn"));
+        assert!(!token.is("let"));
+    }
+
+    #[test]
+    fn widen_to_lines_and_narrow_to_trimmed_use_the_source() {
+        let source = "a\n  名 \nb";
+        let sliced = Sliced::new((), 4..7, source);
+        assert_eq!(&source[sliced.widen_to_lines()], "  名 ");
+        assert_eq!(
+            &source[Sliced::new((), 2..8, source).narrow_to_trimmed()],
+            "名"
+        );
+    }
+
+    #[test]
+    fn as_tuple_borrows_every_part() {
+        let source = "let x";
+        let sliced = Sliced::new(String::from("x"), 4..5, source);
+        let (inner, span, borrowed_source) = sliced.as_tuple();
+        assert!(std::ptr::eq(inner, &sliced.inner));
+        assert_eq!(
+            (inner.as_str(), span, borrowed_source),
+            ("x", &(4..5), source)
+        );
+    }
+
+    fn unescape(text: &str) -> Cow<'_, str> {
+        if text.contains('\\') {
+            Cow::Owned(text.replace("\\n", "\n"))
+        } else {
+            Cow::Borrowed(text)
+        }
+    }
+
+    #[test]
+    fn mapped_text_borrows_unchanged_text() {
+        let source = "\"plain\" \"a\\nb\"";
+        let plain = Sliced::new(&source[1..6], 1..6, source).mapped_text(unescape);
+        assert!(matches!(plain.inner, Cow::Borrowed("plain")));
+        assert_eq!(plain.span(), 1..6);
+    }
+
+    #[test]
+    fn mapped_text_owns_transformed_text() {
+        let source = "\"plain\" \"a\\nb\"";
+        let escaped = Sliced::new(&source[9..13], 9..13, source).mapped_text(unescape);
+        assert!(matches!(&escaped.inner, Cow::Owned(text) if text == "a\nb"));
+        assert_eq!(escaped.slice(), "a\\nb");
+    }
+
+    #[test]
+    fn supports_an_empty_source() {
+        let sliced = Sliced::new((), 0..0, "");
+        assert_eq!(sliced.slice(), "");
+        assert_eq!(sliced.line_col(), (1, 1));
+        #[allow(clippy::single_range_in_vec_init)]
+        let expected = [0..0];
+        assert_eq!(sliced.line_spans(), expected);
+        assert_eq!((sliced.before(), sliced.after()), ("", ""));
+        assert_eq!(sliced.char_len(), 0);
+        assert_eq!(sliced.widen_to_lines(), 0..0);
+        assert_eq!(sliced.to_line_col_range(), (1, 1)..(1, 1));
+    }
+
+    #[test]
+    fn first_and_second_borrow_the_parts_of_a_pair() {
+        let token = Sliced::new(('n', 42), 4..6, "let 42");
+        assert_eq!((*token.first(), *token.second()), ('n', 42));
+        assert_eq!(token.slice(), "42");
+    }
+
+    #[test]
+    fn subspan_carves_a_child_on_char_boundaries() {
+        let source = "let é = 1;";
+        let parent = Sliced::new((), 4..10, source);
+        assert_eq!(parent.subspan(&(0..2)), Some(4..6));
+        assert_eq!(&source[parent.subspan(&(3..4)).unwrap()], "=");
+        assert_eq!(parent.subspan(&(1..2)), None);
+        assert_eq!(parent.subspan(&(5..7)), None);
+    }
+}
@@ -0,0 +1,122 @@
+use std::{
+    fmt::Debug,
+    ops::{Deref, DerefMut},
+    str::Utf8Error,
+};
+
+use crate::Span;
+
+/// A value of type `T` associated with a slice of a source byte buffer.
+///
+/// This is the equivalent of [`Sliced`](struct@crate::Sliced) for sources which aren't
+/// guaranteed to be valid UTF-8.
+#[derive(Clone, PartialEq)]
+pub struct SlicedBytes<'source, T> {
+    pub inner: T,
+    span: Span,
+    source: &'source [u8],
+}
+
+impl<'source, T> SlicedBytes<'source, T> {
+    /// Creates a new `SlicedBytes` value.
+    ///
+    /// # Parameters
+    /// - `inner`: The value to be wrapped.
+    /// - `span`: The range in the source buffer that corresponds to the value.
+    /// - `source`: The source buffer that the wrapped value corresponds to a slice of.
+    pub fn new(inner: T, span: Span, source: &'source [u8]) -> Self {
+        Self {
+            inner,
+            span,
+            source,
+        }
+    }
+
+    /// Consume `self` and return the inner, wrapped value
+    pub fn unwrap(self) -> T {
+        self.inner
+    }
+
+    /// The span associated with the wrapped value.
+    pub fn span(&self) -> Span {
+        self.span.clone()
+    }
+
+    /// The slice associated with the wrapped value.
+    pub fn slice(&self) -> &'source [u8] {
+        &self.source[self.span.clone()]
+    }
+
+    /// The slice associated with the wrapped value, decoded as UTF-8.
+    ///
+    /// Returns an error if the slice is not valid UTF-8.
+    pub fn try_as_str(&self) -> Result<&'source str, Utf8Error> {
+        std::str::from_utf8(self.slice())
+    }
+
+    /// The source buffer associated with the wrapped value.
+    pub fn source(&self) -> &'source [u8] {
+        self.source
+    }
+}
+
+impl<T> Deref for SlicedBytes<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl<T> DerefMut for SlicedBytes<'_, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.inner
+    }
+}
+
+/// Unlike a derived implementation this does not print the source buffer.
+impl<T: Debug> Debug for SlicedBytes<'_, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SlicedBytes")
+            .field("inner", &self.inner)
+            .field("span", &self.span)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &[u8] = b"GIF89a\xff\xfe name=\xc3\xa9";
+
+    #[test]
+    fn slices_the_byte_buffer() {
+        let header = SlicedBytes::new("magic", 0..6, SOURCE);
+        assert_eq!(header.slice(), b"GIF89a");
+        assert_eq!(header.source(), SOURCE);
+    }
+
+    #[test]
+    fn try_as_str_decodes_a_valid_utf8_subrange() {
+        let name = SlicedBytes::new((), 9..16, SOURCE);
+        assert_eq!(name.try_as_str(), Ok("name=é"));
+    }
+
+    #[test]
+    fn try_as_str_rejects_an_invalid_utf8_subrange() {
+        let garbage = SlicedBytes::new((), 6..8, SOURCE);
+        assert!(garbage.try_as_str().is_err());
+        let split_char = SlicedBytes::new((), 9..15, SOURCE);
+        assert_eq!(split_char.try_as_str().unwrap_err().valid_up_to(), 5);
+    }
+
+    #[test]
+    fn debug_does_not_print_the_source() {
+        let header = SlicedBytes::new(1, 0..6, SOURCE);
+        assert_eq!(
+            format!("{:?}", header),
+            "SlicedBytes { inner: 1, span: 0..6 }"
+        );
+    }
+}
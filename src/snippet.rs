@@ -0,0 +1,111 @@
+use crate::{
+    span::{char_count, line_bounds, widen_to_lines},
+    Sliced,
+};
+
+/// The text of a slice along with its position and surrounding lines, for error reports.
+///
+/// Created by [`Sliced::snippet`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Snippet<'source> {
+    /// The text of the slice.
+    pub slice: &'source str,
+    /// The 1-based line on which the slice starts.
+    pub line: usize,
+    /// The 1-based column, in `char`s, at which the slice starts.
+    pub col: usize,
+    /// The line before the one on which the slice starts, or `""` if there is none.
+    pub context_before: &'source str,
+    /// The line after the one on which the slice ends, or `""` if there is none.
+    pub context_after: &'source str,
+}
+
+impl<'source, T> Sliced<'source, T> {
+    /// Gathers the text of the slice, its line and column, and the lines either side of it.
+    ///
+    /// Newlines are excluded from the context lines. A slice spanning several lines has the line
+    /// before its first line and the line after its last line as context. Over an empty source,
+    /// the slice and both context lines are `""`, at line and column `1`.
+    pub fn snippet(&self) -> Snippet<'source> {
+        let source = self.source();
+        let bytes = source.as_bytes();
+        let span = self.span();
+        let lines = widen_to_lines(&span, source);
+
+        let line = bytes[..lines.start].iter().filter(|&&b| b == b'\n').count() + 1;
+        let col = char_count(&bytes[lines.start..span.start]) + 1;
+        let context_before = match lines.start.checked_sub(1) {
+            Some(newline) => &source[line_bounds(source, newline)],
+            None => "",
+        };
+        let context_after = if lines.end < source.len() {
+            &source[line_bounds(source, lines.end + 1)]
+        } else {
+            ""
+        };
+        Snippet {
+            slice: self.slice(),
+            line,
+            col,
+            context_before,
+            context_after,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = "fn main() {\n    let é = 1;\n}";
+
+    #[test]
+    fn gathers_every_field_for_a_known_span() {
+        let snippet = Sliced::new((), 20..22, SOURCE).snippet();
+        assert_eq!(
+            snippet,
+            Snippet {
+                slice: "é",
+                line: 2,
+                col: 9,
+                context_before: "fn main() {",
+                context_after: "}",
+            }
+        );
+    }
+
+    #[test]
+    fn has_no_context_before_the_first_or_after_the_last_line() {
+        let first = Sliced::new((), 3..7, SOURCE).snippet();
+        assert_eq!((first.slice, first.line, first.col), ("main", 1, 4));
+        assert_eq!(first.context_before, "");
+        assert_eq!(first.context_after, "    let é = 1;");
+
+        let last = Sliced::new((), 28..29, SOURCE).snippet();
+        assert_eq!((last.line, last.col), (3, 1));
+        assert_eq!(last.context_before, "    let é = 1;");
+        assert_eq!(last.context_after, "");
+    }
+
+    #[test]
+    fn uses_the_lines_around_a_multi_line_slice() {
+        let source = "a\nb\nc\nd";
+        let snippet = Sliced::new((), 2..5, source).snippet();
+        assert_eq!(snippet.slice, "b\nc");
+        assert_eq!((snippet.context_before, snippet.context_after), ("a", "d"));
+    }
+
+    #[test]
+    fn supports_an_empty_source() {
+        assert_eq!(
+            Sliced::new((), 0..0, "").snippet(),
+            Snippet {
+                slice: "",
+                line: 1,
+                col: 1,
+                context_before: "",
+                context_after: "",
+            }
+        );
+    }
+}
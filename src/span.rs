@@ -0,0 +1,315 @@
+use std::{cmp::Ordering, ops::Range};
+
+use crate::{Span, SpanError};
+
+/// Types which are associated with a span in the source text.
+pub trait HasSpan {
+    /// The span associated with `self`.
+    fn span(&self) -> Span;
+}
+
+impl HasSpan for Span {
+    fn span(&self) -> Span {
+        self.clone()
+    }
+}
+
+/// Computes the smallest span covering every span in `spans`.
+///
+/// Returns `0..0` if `spans` is empty.
+pub fn span_union(spans: &[Span]) -> Span {
+    match spans.split_first() {
+        Some((first, rest)) => rest.iter().fold(first.clone(), |acc, span| {
+            acc.start.min(span.start)..acc.end.max(span.end)
+        }),
+        None => 0..0,
+    }
+}
+
+/// Finds the span from the `open` delimiter at byte `start` of `source` to its matching `close`
+/// delimiter, inclusive, respecting nesting.
+///
+/// Returns `None` if `source` has no `open` delimiter at `start` or it is never closed. If
+/// `open` and `close` are the same, the next occurrence closes the region.
+pub fn matched_region(source: &str, open: char, close: char, start: usize) -> Option<Span> {
+    let rest = source.get(start..).filter(|rest| rest.starts_with(open))?;
+    let mut depth = 0usize;
+    for (i, c) in rest.char_indices() {
+        if c == close && depth > 0 {
+            depth -= 1;
+            if depth == 0 {
+                return Some(start..start + i + c.len_utf8());
+            }
+        } else if c == open {
+            depth += 1;
+        }
+    }
+    None
+}
+
+/// Computes the 1-based line and column of the byte `offset` in `source`.
+///
+/// Columns are counted in `char`s. Offsets past the end of `source` are clamped to its length.
+pub(crate) fn line_col(source: &str, offset: usize) -> (usize, usize) {
+    let before = &source.as_bytes()[..offset.min(source.len())];
+    let line_start = before
+        .iter()
+        .rposition(|&b| b == b'\n')
+        .map_or(0, |i| i + 1);
+    let line = before[..line_start].iter().filter(|&&b| b == b'\n').count() + 1;
+    (line, char_count(&before[line_start..]) + 1)
+}
+
+/// Converts a range of 1-based `(line, column)` positions in `source` back to a span.
+///
+/// This is the inverse of computing [`line_col`](crate::Sliced::line_col) for both ends of a
+/// span, with columns counted in `char`s. A column may be one past the last `char` of its line,
+/// which is the position of the line's newline or the end of `source`. Returns `None` if either
+/// position is not in `source`.
+pub fn from_line_col_range(range: Range<(usize, usize)>, source: &str) -> Option<Span> {
+    Some(offset_of(source, range.start)?..offset_of(source, range.end)?)
+}
+
+/// The byte offset of the 1-based `(line, column)` position in `source`, if there is one.
+fn offset_of(source: &str, (line, column): (usize, usize)) -> Option<usize> {
+    let line_start = match line.checked_sub(2) {
+        Some(newlines) => source.match_indices('\n').nth(newlines)?.0 + 1,
+        None if line == 1 => 0,
+        None => return None,
+    };
+    let line_end = source[line_start..]
+        .find('\n')
+        .map_or(source.len(), |i| line_start + i);
+    let text = &source[line_start..line_end];
+    text.char_indices()
+        .map(|(i, _)| i)
+        .chain(std::iter::once(text.len()))
+        .nth(column.checked_sub(1)?)
+        .map(|i| line_start + i)
+}
+
+/// Counts the `char`s in UTF-8 encoded `bytes` by counting the bytes which begin a `char`, so
+/// `bytes` may start or end inside a `char`.
+pub(crate) fn char_count(bytes: &[u8]) -> usize {
+    bytes.iter().filter(|&&b| (b & 0xC0) != 0x80).count()
+}
+
+/// The span of the line of `source` containing the byte `offset`, excluding its newline.
+///
+/// Offsets past the end of `source` are clamped to its length.
+pub(crate) fn line_bounds(source: &str, offset: usize) -> Span {
+    let offset = offset.min(source.len());
+    let bytes = source.as_bytes();
+    let start = bytes[..offset]
+        .iter()
+        .rposition(|&b| b == b'\n')
+        .map_or(0, |i| i + 1);
+    let end = bytes[offset..]
+        .iter()
+        .position(|&b| b == b'\n')
+        .map_or(source.len(), |i| offset + i);
+    start..end
+}
+
+/// Shifts both ends of `span` by `delta`, returning `None` on overflow or underflow.
+pub(crate) fn checked_shift(span: &Span, delta: isize) -> Option<Span> {
+    Some(span.start.checked_add_signed(delta)?..span.end.checked_add_signed(delta)?)
+}
+
+/// Checks that `span` is well-ordered, within `source`, and on `char` boundaries.
+pub(crate) fn validate(span: &Span, source: &str) -> Result<(), SpanError> {
+    if span.start > span.end {
+        return Err(SpanError::Inverted(span.clone()));
+    }
+    if span.end > source.len() {
+        return Err(SpanError::OutOfBounds {
+            span: span.clone(),
+            len: source.len(),
+        });
+    }
+    match [span.start, span.end]
+        .into_iter()
+        .find(|&index| !source.is_char_boundary(index))
+    {
+        Some(index) => Err(SpanError::NotCharBoundary {
+            span: span.clone(),
+            index,
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Fixes up `span` after `removed` bytes at `edit_start` are replaced by `inserted` bytes.
+///
+/// Returns `None` if `span` overlaps the replaced bytes, or if the fixed-up span would overflow.
+pub(crate) fn apply_edit(
+    span: &Span,
+    edit_start: usize,
+    removed: usize,
+    inserted: usize,
+) -> Option<Span> {
+    let edit_end = edit_start.saturating_add(removed);
+    if span.end <= edit_start {
+        Some(span.clone())
+    } else if span.start >= edit_end {
+        let start = (span.start - removed).checked_add(inserted)?;
+        let end = (span.end - removed).checked_add(inserted)?;
+        Some(start..end)
+    } else {
+        None
+    }
+}
+
+/// Splits `span` into the sub-span it covers on each line of `source`.
+///
+/// Newlines are excluded from the sub-spans, and a span ending just after a newline doesn't
+/// produce an empty sub-span for the following line.
+pub(crate) fn line_spans(span: &Span, source: &str) -> Vec<Span> {
+    let end = span.end.min(source.len());
+    let mut start = span.start.min(end);
+    let mut spans = Vec::new();
+    loop {
+        match source.as_bytes()[start..end]
+            .iter()
+            .position(|&b| b == b'\n')
+        {
+            Some(newline) => {
+                spans.push(start..start + newline);
+                start += newline + 1;
+                if start >= end {
+                    break spans;
+                }
+            }
+            None => {
+                spans.push(start..end);
+                break spans;
+            }
+        }
+    }
+}
+
+/// Returns `true` if `inner` lies entirely within `outer`.
+pub(crate) fn contains(outer: &Span, inner: &Span) -> bool {
+    outer.start <= inner.start && inner.end <= outer.end
+}
+
+/// Returns `true` if `a` and `b` share at least one position, so empty spans never overlap.
+pub(crate) fn overlaps(a: &Span, b: &Span) -> bool {
+    !a.is_empty() && !b.is_empty() && a.start < b.end && b.start < a.end
+}
+
+/// Returns `true` if `a` and `b` overlap or are adjacent, so an empty span touches any span
+/// which contains or borders its position.
+pub(crate) fn touches(a: &Span, b: &Span) -> bool {
+    a.start <= b.end && b.start <= a.end
+}
+
+/// Extends `span` to the start of its first line and the end of its last line of `source`,
+/// excluding the final newline.
+///
+/// A non-empty span ending just after a newline isn't extended over the following line.
+pub(crate) fn widen_to_lines(span: &Span, source: &str) -> Span {
+    let mut end = span.end.min(source.len());
+    if end > span.start && source.as_bytes()[end - 1] == b'\n' {
+        end -= 1;
+    }
+    line_bounds(source, span.start).start..line_bounds(source, end).end
+}
+
+/// Shrinks `span` to exclude the whitespace at either end of its text in `source`.
+///
+/// A span covering only whitespace shrinks to an empty span at its end, and a span which isn't
+/// valid for `source` is returned unchanged.
+pub(crate) fn narrow_to_trimmed(span: &Span, source: &str) -> Span {
+    let Some(text) = source.get(span.clone()) else {
+        return span.clone();
+    };
+    let start = span.start + (text.len() - text.trim_start().len());
+    let end = span.end - (text.len() - text.trim_end().len());
+    start..end.max(start)
+}
+
+/// Compares the lengths of `a` and `b`, breaking ties by their starts.
+pub(crate) fn cmp_by_len(a: &Span, b: &Span) -> Ordering {
+    a.len().cmp(&b.len()).then(a.start.cmp(&b.start))
+}
+
+/// Translates `relative`, a span relative to the start of `parent`, into an absolute span.
+///
+/// Returns `None` if `relative` is inverted or doesn't lie entirely within `parent`.
+pub(crate) fn subspan(parent: &Span, relative: &Span) -> Option<Span> {
+    if relative.start > relative.end || relative.end > parent.len() {
+        return None;
+    }
+    Some(parent.start + relative.start..parent.start + relative.end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Sliced, Spanned};
+
+    #[test]
+    fn span_union_of_no_spans_is_empty() {
+        assert_eq!(span_union(&[]), 0..0);
+    }
+
+    #[test]
+    fn span_union_of_one_span_is_that_span() {
+        let span = 3..7;
+        assert_eq!(span_union(std::slice::from_ref(&span)), span);
+    }
+
+    #[test]
+    fn span_union_covers_gaps_in_any_order() {
+        assert_eq!(span_union(&[8..10, 2..4, 5..6]), 2..10);
+    }
+
+    #[test]
+    fn line_col_range_round_trips_on_multi_byte_lines() {
+        let source = "名前 = 1;\nlet é = \"ü\";\n";
+        for span in [0..6, 7..11, 12..15, 16..18, 18..24, 3..20, 26..27, 27..27] {
+            let range = Spanned::new((), span.clone()).to_line_col_range(source);
+            assert_eq!(from_line_col_range(range, source), Some(span));
+        }
+    }
+
+    #[test]
+    fn line_col_range_matches_line_col() {
+        let source = "名前 = 1;\nlet é = \"ü\";";
+        let sliced = Sliced::new((), 16..24, source);
+        assert_eq!(sliced.to_line_col_range(), (2, 5)..(2, 11));
+        assert_eq!(sliced.to_line_col_range().start, sliced.line_col());
+    }
+
+    #[test]
+    fn from_line_col_range_rejects_positions_outside_the_source() {
+        let source = "ab\ncd";
+        assert_eq!(from_line_col_range((1, 1)..(2, 3), source), Some(0..5));
+        assert_eq!(from_line_col_range((1, 4)..(2, 1), source), None);
+        assert_eq!(from_line_col_range((1, 1)..(3, 1), source), None);
+        assert_eq!(from_line_col_range((0, 1)..(1, 1), source), None);
+        assert_eq!(from_line_col_range((1, 0)..(1, 1), source), None);
+    }
+
+    #[test]
+    fn matched_region_respects_nesting() {
+        let source = "f(a, (b), [c]) + (d)";
+        assert_eq!(matched_region(source, '(', ')', 1), Some(1..14));
+        assert_eq!(matched_region(source, '(', ')', 5), Some(5..8));
+        assert_eq!(matched_region(source, '[', ']', 10), Some(10..13));
+    }
+
+    #[test]
+    fn matched_region_returns_none_if_unbalanced_or_not_at_a_delimiter() {
+        assert_eq!(matched_region("(a (b)", '(', ')', 0), None);
+        assert_eq!(matched_region("(a)", '(', ')', 1), None);
+        assert_eq!(matched_region("(a)", '(', ')', 9), None);
+    }
+
+    #[test]
+    fn matched_region_handles_identical_and_multi_byte_delimiters() {
+        assert_eq!(matched_region("x = \"abc\";", '"', '"', 4), Some(4..9));
+        assert_eq!(matched_region("«a«b»»", '«', '»', 0), Some(0..10));
+    }
+}
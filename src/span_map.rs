@@ -0,0 +1,217 @@
+use crate::{Span, SpanError, Spanned};
+
+/// A table mapping spans between an original source and a source generated from it by
+/// replacing some of its regions, such as after a preprocessing stage.
+///
+/// Offsets outside the replaced regions map exactly. A span which starts or ends strictly
+/// inside a replaced region can't be mapped. A span covering a whole replaced region maps to a
+/// span covering its replacement. At an insertion point, a span starting there maps to after
+/// the inserted text and a span ending there maps to before it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SpanMap {
+    /// The replaced regions as `(original, generated)` span pairs, in order.
+    edits: Vec<(Span, Span)>,
+}
+
+impl SpanMap {
+    /// Creates a new `SpanMap` with no replaced regions, which maps every span to itself.
+    pub fn new() -> Self {
+        Self { edits: Vec::new() }
+    }
+
+    /// Records that the `original` region of the original source was replaced by
+    /// `replacement_len` bytes in the generated source.
+    ///
+    /// Regions must be recorded in order of position. They may touch, and any number of
+    /// insertions (empty spans) may be recorded at the same position.
+    ///
+    /// Returns an error if `original` is inverted, overlaps or precedes the previously
+    /// recorded region, or if its replacement would end past `usize::MAX`.
+    pub fn push(&mut self, original: Span, replacement_len: usize) -> Result<(), SpanError> {
+        if original.start > original.end {
+            return Err(SpanError::Inverted(original));
+        }
+        let start = match self.edits.last() {
+            Some((previous, _)) if previous.end > original.start => {
+                return Err(SpanError::Overlapping(previous.clone(), original));
+            }
+            Some((previous, generated)) => generated.end.checked_add(original.start - previous.end),
+            None => Some(original.start),
+        };
+        let Some(generated) =
+            start.and_then(|start| Some(start..start.checked_add(replacement_len)?))
+        else {
+            return Err(SpanError::Overflow(original));
+        };
+        self.edits.push((original, generated));
+        Ok(())
+    }
+
+    /// The number of replaced regions.
+    pub fn len(&self) -> usize {
+        self.edits.len()
+    }
+
+    /// Returns `true` if no regions were replaced.
+    pub fn is_empty(&self) -> bool {
+        self.edits.is_empty()
+    }
+
+    /// Maps `span` from the coordinates of the original source to those of the generated
+    /// source, returning `None` if it starts or ends strictly inside a replaced region.
+    pub fn to_generated(&self, span: &Span) -> Option<Span> {
+        map_span(
+            self.edits
+                .iter()
+                .map(|(original, generated)| (original, generated)),
+            span,
+        )
+    }
+
+    /// Maps `span` from the coordinates of the generated source back to those of the original
+    /// source, returning `None` if it starts or ends strictly inside a replacement.
+    pub fn to_original(&self, span: &Span) -> Option<Span> {
+        map_span(
+            self.edits
+                .iter()
+                .map(|(original, generated)| (generated, original)),
+            span,
+        )
+    }
+}
+
+/// Maps `span` through replaced regions given as `(from, to)` span pairs.
+fn map_span<'a>(
+    edits: impl Iterator<Item = (&'a Span, &'a Span)> + Clone,
+    span: &Span,
+) -> Option<Span> {
+    let start = map_offset(edits.clone(), span.start, true)?;
+    let end = map_offset(edits, span.end, false)?;
+    Some(start..end)
+}
+
+/// Maps the `offset` of the start or end of a span through replaced regions.
+fn map_offset<'a>(
+    edits: impl Iterator<Item = (&'a Span, &'a Span)>,
+    offset: usize,
+    is_start: bool,
+) -> Option<usize> {
+    // The ends of the last region before `offset`, which `offset` is moved relative to.
+    let (mut from_end, mut to_end) = (0, 0);
+    for (from, to) in edits {
+        let before = if is_start {
+            offset < from.start || (offset == from.start && !from.is_empty())
+        } else {
+            offset <= from.start
+        };
+        if before {
+            break;
+        }
+        if offset < from.end {
+            return None;
+        }
+        (from_end, to_end) = (from.end, to.end);
+    }
+    to_end.checked_add(offset - from_end)
+}
+
+/// Maps the span of every item from the coordinates of the generated source of `map` back to
+/// those of its original source, see [`SpanMap::to_original`].
+///
+/// Items whose span can't be mapped, because it starts or ends strictly inside generated text,
+/// are dropped. The remaining items keep their relative order.
+pub fn remap_through<T>(items: Vec<Spanned<T>>, map: &SpanMap) -> Vec<Spanned<T>> {
+    items
+        .into_iter()
+        .filter_map(|mut item| {
+            item.set_span(map.to_original(&item.span())?);
+            Some(item)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Maps `"let x = 1;"` to `"let value = 2; "` by replacing `x`, deleting `1` and inserting
+    /// `2` after it, and appending a space.
+    fn map() -> SpanMap {
+        let mut map = SpanMap::new();
+        map.push(4..5, 5).unwrap();
+        map.push(8..9, 0).unwrap();
+        map.push(9..9, 1).unwrap();
+        map.push(10..10, 1).unwrap();
+        map
+    }
+
+    #[test]
+    fn maps_spans_outside_and_around_replaced_regions() {
+        let map = map();
+        assert_eq!(map.len(), 4);
+        assert_eq!(map.to_generated(&(0..3)), Some(0..3));
+        assert_eq!(map.to_generated(&(4..5)), Some(4..9));
+        assert_eq!(map.to_generated(&(6..7)), Some(10..11));
+        assert_eq!(map.to_generated(&(9..10)), Some(13..14));
+        assert_eq!(map.to_original(&(4..9)), Some(4..5));
+        assert_eq!(map.to_original(&(13..14)), Some(9..10));
+        assert_eq!(map.to_original(&(0..15)), Some(0..10));
+    }
+
+    #[test]
+    fn spans_inside_a_replaced_region_cannot_be_mapped() {
+        let map = map();
+        assert_eq!(map.to_original(&(5..7)), None);
+        assert_eq!(map.to_original(&(7..10)), None);
+        assert_eq!(map.to_original(&(3..6)), None);
+        assert_eq!(map.to_generated(&(4..4)), Some(4..4));
+        assert_eq!(map.to_original(&(14..15)), Some(10..10));
+    }
+
+    #[test]
+    fn remap_through_restores_original_spans_and_drops_the_rest() {
+        let generated = vec![
+            Spanned::new("let", 0..3),
+            Spanned::new("value", 4..9),
+            Spanned::new("alu", 5..8),
+            Spanned::new("2", 12..13),
+            Spanned::new(";", 13..14),
+        ];
+        assert_eq!(
+            remap_through(generated, &map()),
+            [
+                Spanned::new("let", 0..3),
+                Spanned::new("value", 4..5),
+                Spanned::new("2", 9..9),
+                Spanned::new(";", 9..10),
+            ]
+        );
+    }
+
+    #[test]
+    fn push_rejects_misordered_regions() {
+        let mut map = SpanMap::new();
+        map.push(4..6, 1).unwrap();
+        assert_eq!(map.push(5..7, 1), Err(SpanError::Overlapping(4..6, 5..7)));
+        assert_eq!(map.push(0..1, 1), Err(SpanError::Overlapping(4..6, 0..1)));
+        #[allow(clippy::reversed_empty_ranges)]
+        let inverted = 9..8;
+        assert_eq!(
+            map.push(inverted.clone(), 1),
+            Err(SpanError::Inverted(inverted))
+        );
+    }
+
+    #[test]
+    fn push_reports_overflow_near_usize_max() {
+        let mut map = SpanMap::new();
+        let region = usize::MAX - 1..usize::MAX - 1;
+        assert_eq!(
+            map.push(region.clone(), 2),
+            Err(SpanError::Overflow(region))
+        );
+        map.push(0..0, usize::MAX).unwrap();
+        assert_eq!(map.push(1..1, 0), Err(SpanError::Overflow(1..1)));
+        assert!(map.to_generated(&(0..1)).is_none());
+    }
+}
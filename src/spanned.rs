@@ -1,12 +1,25 @@
 use std::{
+    cmp::Ordering,
     fmt::Display,
-    ops::{Deref, DerefMut},
+    hash::{Hash, Hasher},
+    iter::{once, Once},
+    ops::{Deref, DerefMut, Range},
+    sync::Arc,
 };
 
-use crate::Span;
+use crate::{
+    edit,
+    hash::FnvHasher,
+    span::{self, apply_edit, checked_shift, line_col, line_spans},
+    span_union, HasSpan, Span, SpanError,
+};
 
 /// A value of type `T` associated with a span in the source text.
+///
+/// With the `serde` feature enabled, this is serialized as a struct with `inner` and `span`
+/// fields. See the `compact` module for a more compact representation.
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Spanned<T> {
     pub inner: T,
     span: Span,
@@ -22,15 +35,313 @@ impl<T> Spanned<T> {
         Self { inner, span }
     }
 
+    /// Creates a new `Spanned` value whose span covers all of `children`.
+    ///
+    /// If `children` is empty the span is `0..0`, see [`span_union`].
+    pub fn wrapping(inner: T, children: &[Span]) -> Self {
+        Self::new(inner, span_union(children))
+    }
+
+    /// Creates a new `Spanned` value associated with the same span as `other`.
+    ///
+    /// Useful for attributing a computed value to the location of the value it came from.
+    pub fn borrow_span_from<U>(inner: T, other: &Spanned<U>) -> Self {
+        Self::new(inner, other.span())
+    }
+
+    /// Creates a new `Spanned` value with no real location, using the span `0..0`.
+    ///
+    /// Useful for values which don't correspond to any source text, such as generated nodes.
+    pub fn synthetic(inner: T) -> Self {
+        Self::new(inner, 0..0)
+    }
+
     /// Consume `self` and return the inner, wrapped value
     pub fn unwrap(self) -> T {
         self.inner
     }
 
+    /// Returns an iterator yielding `self` exactly once.
+    ///
+    /// Useful for chaining a single value onto an iterator of `Spanned` values.
+    pub fn once(self) -> Once<Spanned<T>> {
+        once(self)
+    }
+
+    /// Maps a `&Spanned<T>` to a `Spanned<U>` by applying `f` to a reference to the wrapped value.
+    ///
+    /// `self` is not consumed, so this is useful for deriving a new value without cloning.
+    pub fn map_ref<U>(&self, f: impl FnOnce(&T) -> U) -> Spanned<U> {
+        Spanned::new(f(&self.inner), self.span.clone())
+    }
+
+    /// Borrows a part of the wrapped value, such as a field, keeping the span.
+    ///
+    /// Unlike [`Spanned::map_ref`], the result borrows from `self`, so the part needn't be cloned.
+    pub fn project<U>(&self, f: impl FnOnce(&T) -> &U) -> Spanned<&U> {
+        Spanned::new(f(&self.inner), self.span.clone())
+    }
+
+    /// Maps a `Spanned<T>` to a `Spanned<U>` by applying `f` to the wrapped value and the span.
+    ///
+    /// The result keeps the original span.
+    pub fn map_with_span<U>(self, f: impl FnOnce(T, Span) -> U) -> Spanned<U> {
+        let span = self.span;
+        Spanned::new(f(self.inner, span.clone()), span)
+    }
+
+    /// Replaces the wrapped value with `new`, which may be of a different type, keeping the span.
+    ///
+    /// This reads better than `map_with_span(|_, _| new)` when reclassifying a value, such as
+    /// turning a keyword token into an identifier.
+    pub fn retag<U>(self, new: U) -> Spanned<U> {
+        Spanned::new(new, self.span)
+    }
+
+    /// Renders the wrapped value with [`ToString::to_string`], keeping the span.
+    ///
+    /// This is the same as `map_ref(ToString::to_string)`.
+    pub fn display_to_spanned(&self) -> Spanned<String>
+    where
+        T: Display,
+    {
+        self.map_ref(ToString::to_string)
+    }
+
+    /// Consume `self` and return the wrapped value and its span.
+    pub fn into_parts(self) -> (T, Span) {
+        (self.inner, self.span)
+    }
+
+    /// Borrows the wrapped value and its span.
+    ///
+    /// This is the borrowing equivalent of [`Spanned::into_parts`], and doesn't clone the span.
+    pub fn as_tuple(&self) -> (&T, &Span) {
+        (&self.inner, &self.span)
+    }
+
     /// The span associated with the wrapped value.
     pub fn span(&self) -> Span {
         self.span.clone()
     }
+
+    /// The span associated with the wrapped value, as a plain `Range<usize>`.
+    ///
+    /// This is a synonym of [`Spanned::span`].
+    pub fn range(&self) -> Range<usize> {
+        self.span()
+    }
+
+    /// Returns an adapter which displays the wrapped value followed by its byte span.
+    pub fn located(&self) -> impl Display + '_
+    where
+        T: Display,
+    {
+        Located(self)
+    }
+
+    /// Replaces the span associated with the wrapped value.
+    pub fn set_span(&mut self, span: Span) {
+        self.span = span;
+    }
+
+    /// Moves the span by `delta` bytes, keeping its length.
+    ///
+    /// # Panics
+    /// Panics if either end of the span overflows or underflows, see [`Spanned::checked_shift`].
+    pub fn shift(self, delta: isize) -> Self {
+        self.checked_shift(delta)
+            .expect("span shift overflowed or underflowed")
+    }
+
+    /// Moves the span by `delta` bytes, keeping its length.
+    ///
+    /// Returns `None` if either end of the span overflows or underflows.
+    pub fn checked_shift(self, delta: isize) -> Option<Self> {
+        let span = checked_shift(&self.span, delta)?;
+        Some(Self::new(self.inner, span))
+    }
+
+    /// Translates `relative`, a span relative to the start of the span, into an absolute span,
+    /// such as the span of a child within a matched region.
+    ///
+    /// Returns `None` if `relative` is inverted or doesn't lie entirely within the span.
+    pub fn subspan(&self, relative: &Span) -> Option<Span> {
+        span::subspan(&self.span, relative)
+    }
+
+    /// Converts a span relative to a fragment of a source, such as a template, into an absolute
+    /// span by moving it forward by `origin`, the offset of the fragment.
+    ///
+    /// This is the same as shifting the span by `origin`, but named for intent.
+    ///
+    /// # Panics
+    /// Panics if either end of the span overflows.
+    pub fn anchored(self, origin: usize) -> Self {
+        let anchor = |offset: usize| {
+            offset
+                .checked_add(origin)
+                .expect("anchored span overflowed")
+        };
+        let span = anchor(self.span.start)..anchor(self.span.end);
+        Self::new(self.inner, span)
+    }
+
+    /// Returns a copy of `source` with the text covered by the span replaced by `replacement`.
+    ///
+    /// # Panics
+    /// Panics if the span is not valid for `source`, see [`Spanned::validate`].
+    pub fn replace_in(&self, source: &str, replacement: &str) -> String {
+        edit::replace(source, &self.span, replacement)
+    }
+
+    /// Splits the span into the sub-span it covers on each line of `source`.
+    ///
+    /// A span within a single line produces a single sub-span. Newlines are excluded from the
+    /// sub-spans, and a span ending just after a newline doesn't produce an empty sub-span for
+    /// the following line.
+    pub fn line_spans(&self, source: &str) -> Vec<Span> {
+        line_spans(&self.span, source)
+    }
+
+    /// The span extended to the start of its first line and the end of its last line of
+    /// `source`, excluding the final newline.
+    ///
+    /// A non-empty span ending just after a newline isn't extended over the following line.
+    pub fn widen_to_lines(&self, source: &str) -> Span {
+        span::widen_to_lines(&self.span, source)
+    }
+
+    /// The span shrunk to exclude the whitespace at either end of its text in `source`.
+    ///
+    /// A span covering only whitespace shrinks to an empty span at its end, and a span which
+    /// isn't valid for `source` is returned unchanged.
+    pub fn narrow_to_trimmed(&self, source: &str) -> Span {
+        span::narrow_to_trimmed(&self.span, source)
+    }
+
+    /// Grows the span to also cover the span of `child`.
+    pub fn extend_to_cover(&mut self, child: &impl HasSpan) {
+        self.span = span_union(&[self.span.clone(), child.span()]);
+    }
+
+    /// The 1-based `(line, column)` positions of the start and end of the span in `source`.
+    ///
+    /// Columns are counted in `char`s, and offsets past the end of `source` are clamped to its
+    /// length. See [`from_line_col_range`](crate::from_line_col_range) for the inverse.
+    pub fn to_line_col_range(&self, source: &str) -> Range<(usize, usize)> {
+        line_col(source, self.span.start)..line_col(source, self.span.end)
+    }
+
+    /// Returns `true` if the span shares at least one position with `other`.
+    ///
+    /// Adjacent spans such as `1..3` and `3..5` don't overlap, and an empty span never overlaps
+    /// anything, even a span containing its position.
+    pub fn overlaps(&self, other: &Span) -> bool {
+        span::overlaps(&self.span, other)
+    }
+
+    /// Returns `true` if the span overlaps `other` or is adjacent to it, so that
+    /// `self.end == other.start` or `other.end == self.start`.
+    ///
+    /// Unlike [`overlaps`](Self::overlaps), an empty span touches any span which contains or
+    /// borders its position, so `3..3` touches `1..5`, `1..3`, `3..5` and `3..3`.
+    pub fn touches(&self, other: &Span) -> bool {
+        span::touches(&self.span, other)
+    }
+
+    /// Returns `true` if the span's start is greater than its end.
+    pub fn is_inverted(&self) -> bool {
+        self.span.start > self.span.end
+    }
+
+    /// Swaps the start and end of the span if it is inverted.
+    ///
+    /// This only reorders the ends of the span, it never clamps them to the bounds of a source.
+    pub fn normalize(mut self) -> Self {
+        if self.is_inverted() {
+            self.span = self.span.end..self.span.start;
+        }
+        self
+    }
+
+    /// Shortens the span to at most `max_len` bytes from its start.
+    pub fn truncate(mut self, max_len: usize) -> Self {
+        self.span.end = self.span.end.min(self.span.start.saturating_add(max_len));
+        self
+    }
+
+    /// Fixes up the span after an edit replaced `removed` bytes at `edit_start` with `inserted`
+    /// bytes.
+    ///
+    /// A span entirely before the edit is left untouched, and a span entirely after the edit is
+    /// moved to account for the change in length. Returns `false` if the span overlaps the
+    /// replaced bytes (or can't be moved without overflowing) and so has been invalidated by
+    /// the edit, in which case the span is left untouched.
+    pub fn apply_edit(&mut self, edit_start: usize, removed: usize, inserted: usize) -> bool {
+        match apply_edit(&self.span, edit_start, removed, inserted) {
+            Some(span) => {
+                self.span = span;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns `true` if the span is `0..0`, as created by [`Spanned::synthetic`].
+    pub fn is_synthetic(&self) -> bool {
+        self.span == (0..0)
+    }
+
+    /// Checks that the span is well-ordered, within `source`, and lies on `char` boundaries.
+    pub fn validate(&self, source: &str) -> Result<(), SpanError> {
+        span::validate(&self.span, source)
+    }
+
+    /// Returns a clone of `self` associated with `span` instead, leaving `self` untouched.
+    pub fn respanned(&self, span: Span) -> Spanned<T>
+    where
+        T: Clone,
+    {
+        Spanned::new(self.inner.clone(), span)
+    }
+
+    /// Compares the lengths of the spans of `self` and `other`, breaking ties by the start of
+    /// the span.
+    ///
+    /// The wrapped values are ignored. An inverted span has a length of `0`.
+    pub fn cmp_by_len(&self, other: &Self) -> Ordering {
+        span::cmp_by_len(&self.span, &other.span)
+    }
+
+    /// Returns `true` if the wrapped value is equal to `other`, ignoring the span.
+    pub fn eq_value(&self, other: &T) -> bool
+    where
+        T: PartialEq,
+    {
+        self.inner == *other
+    }
+
+    /// Hashes the wrapped value and the span into a key which is identical from run to run and
+    /// across platforms, making it suitable for persistent caches.
+    ///
+    /// The key is only as stable as the [`Hash`] implementation of `T`. Derived implementations
+    /// and those of integers, `str` and `String` are stable across platforms.
+    pub fn stable_key(&self) -> u64
+    where
+        T: Hash,
+    {
+        let mut hasher = FnvHasher::new();
+        self.inner.hash(&mut hasher);
+        hasher.write_u64(self.span.start as u64);
+        hasher.write_u64(self.span.end as u64);
+        hasher.finish()
+    }
+
+    /// Maps a `Spanned<T>` to a `Spanned<Arc<T>>` so the wrapped value can be shared, e.g. across threads.
+    pub fn shared(self) -> Spanned<Arc<T>> {
+        Spanned::new(Arc::new(self.inner), self.span)
+    }
 }
 
 impl<T, E> Spanned<Result<T, E>> {
@@ -45,6 +356,45 @@ impl<T, E> Spanned<Result<T, E>> {
             Err(e) => Err(Spanned::new(e, self.span)),
         }
     }
+
+    /// Maps a `Spanned<Result<T, E>>` to a `Spanned<T>`, replacing an error with a recovery value.
+    ///
+    /// `Ok(t)` is passed through unchanged, while `Err(e)` is replaced by `f(e)`. The span is
+    /// kept in both cases.
+    pub fn recover(self, f: impl FnOnce(E) -> T) -> Spanned<T> {
+        Spanned::new(self.inner.unwrap_or_else(f), self.span)
+    }
+
+    /// Maps a `Spanned<Result<T, E>>` to a `Spanned<T>`, replacing an error with `T::default()`.
+    ///
+    /// This is the same as [`Spanned::recover`], but discards the error.
+    pub fn or_default(self) -> Spanned<T>
+    where
+        T: Default,
+    {
+        self.recover(|_| T::default())
+    }
+
+    /// Maps both arms of a `Spanned<Result<T, E>>`, applying `ok` to a value or `err` to an error.
+    pub fn map_both<U, F>(
+        self,
+        ok: impl FnOnce(T) -> U,
+        err: impl FnOnce(E) -> F,
+    ) -> Spanned<Result<U, F>> {
+        let inner = match self.inner {
+            Ok(t) => Ok(ok(t)),
+            Err(e) => Err(err(e)),
+        };
+        Spanned::new(inner, self.span)
+    }
+
+    /// Maps the error of a `Spanned<Result<T, E>>`, passing the span to `f` alongside it.
+    ///
+    /// This allows the span to be embedded into error types which carry their own location.
+    pub fn map_err_span(self, f: impl FnOnce(E, Span) -> E) -> Self {
+        let span = self.span;
+        Spanned::new(self.inner.map_err(|e| f(e, span.clone())), span)
+    }
 }
 
 impl<T> Spanned<Option<T>> {
@@ -58,6 +408,39 @@ impl<T> Spanned<Option<T>> {
             None => None,
         }
     }
+
+    /// Maps the `Some` value of a `Spanned<Option<T>>` by applying `f` to it.
+    ///
+    /// Unlike `unzip`, the span is kept whether the value is `Some` or `None`.
+    pub fn map_opt<U>(self, f: impl FnOnce(T) -> U) -> Spanned<Option<U>> {
+        Spanned::new(self.inner.map(f), self.span)
+    }
+}
+
+impl<K, T> Spanned<(K, T)> {
+    /// The first element of the wrapped pair, such as the kind of a token.
+    pub fn first(&self) -> &K {
+        &self.inner.0
+    }
+
+    /// The second element of the wrapped pair, such as the value of a token.
+    pub fn second(&self) -> &T {
+        &self.inner.1
+    }
+}
+
+impl<T> Spanned<Vec<T>> {
+    /// Maps a `Spanned<Vec<T>>` to a `Spanned<Vec<U>>` by applying `f` to each element, keeping
+    /// the span of the list.
+    pub fn map_each<U>(self, f: impl FnMut(T) -> U) -> Spanned<Vec<U>> {
+        Spanned::new(self.inner.into_iter().map(f).collect(), self.span)
+    }
+
+    /// Keeps only the elements for which `pred` returns `true`, keeping the span of the list.
+    pub fn filter_each(mut self, pred: impl FnMut(&T) -> bool) -> Spanned<Vec<T>> {
+        self.inner.retain(pred);
+        self
+    }
 }
 
 impl<T> Spanned<&mut T> {
@@ -108,6 +491,19 @@ impl<T> Spanned<&T> {
     }
 }
 
+impl<T> Spanned<Arc<T>> {
+    /// Maps a `Spanned<Arc<T>>` to a `Spanned<T>`, cloning the wrapped value if it is still shared.
+    pub fn unshared(self) -> Spanned<T>
+    where
+        T: Clone,
+    {
+        Spanned::new(
+            Arc::try_unwrap(self.inner).unwrap_or_else(|arc| T::clone(&arc)),
+            self.span,
+        )
+    }
+}
+
 impl<T> Clone for Spanned<T>
 where
     T: Clone,
@@ -170,14 +566,612 @@ impl<'a, T> From<&'a mut Spanned<T>> for Spanned<&'a mut T> {
     }
 }
 
+impl<T> From<&Spanned<T>> for Span {
+    fn from(value: &Spanned<T>) -> Self {
+        value.span()
+    }
+}
+
+impl<T> HasSpan for Spanned<T> {
+    fn span(&self) -> Span {
+        self.span.clone()
+    }
+}
+
+/// Iterates over `self` as a single item, this does not iterate over the wrapped value.
+impl<T> IntoIterator for Spanned<T> {
+    type Item = Spanned<T>;
+    type IntoIter = Once<Spanned<T>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        once(self)
+    }
+}
+
 impl<T: PartialEq> PartialEq for Spanned<T> {
     fn eq(&self, other: &Self) -> bool {
         self.inner == other.inner && self.span == other.span
     }
 }
 
+/// Displays a `Spanned` value followed by its byte span, see [`Spanned::located`].
+struct Located<'a, T>(&'a Spanned<T>);
+
+impl<T: Display> Display for Located<'_, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({:?})", self.0.inner, self.0.span)
+    }
+}
+
 impl<T: Display> Display for Spanned<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}(\"{:?}\")", self.inner, self.span)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn eq_value_compares_only_the_inner_value() {
+        let spanned = Spanned::new(4, 1..2);
+        assert!(spanned.eq_value(&4));
+        assert!(!spanned.eq_value(&5));
+    }
+
+    #[test]
+    fn into_parts_round_trips_through_new() {
+        let spanned = Spanned::new("ident", 3..8);
+        let (inner, span) = spanned.clone().into_parts();
+        assert_eq!(inner, "ident");
+        assert_eq!(span, 3..8);
+        assert_eq!(Spanned::new(inner, span), spanned);
+    }
+
+    #[test]
+    fn wrapping_covers_every_child() {
+        assert_eq!(Spanned::wrapping((), &[4..6, 0..1]).span(), 0..6);
+        assert_eq!(Spanned::wrapping((), &[]).span(), 0..0);
+    }
+
+    #[test]
+    fn recover_keeps_the_span_of_both_arms() {
+        let ok: Spanned<Result<i32, &str>> = Spanned::new(Ok(1), 2..4);
+        assert_eq!(ok.recover(|_| 0), Spanned::new(1, 2..4));
+        let err: Spanned<Result<i32, &str>> = Spanned::new(Err("bad"), 2..4);
+        assert_eq!(err.recover(|e| e.len() as i32), Spanned::new(3, 2..4));
+    }
+
+    #[test]
+    fn debug_shows_the_inner_value_and_span() {
+        assert_eq!(
+            format!("{:?}", Spanned::new('a', 3..7)),
+            "Spanned { inner: 'a', span: 3..7 }"
+        );
+    }
+
+    #[test]
+    fn shared_values_can_be_sent_across_threads() {
+        fn assert_send_sync<T: Send + Sync>(_: &T) {}
+        let shared = Spanned::new(String::from("node"), 4..8).shared();
+        assert_send_sync(&shared);
+        let clone = shared.clone();
+        let handle = std::thread::spawn(move || clone.map_ref(|node| node.len()));
+        assert_eq!(handle.join().unwrap(), Spanned::new(4, 4..8));
+        assert_eq!(shared.unshared(), Spanned::new(String::from("node"), 4..8));
+    }
+
+    #[test]
+    fn respanned_leaves_the_original_untouched() {
+        let original = Spanned::new("x", 1..2);
+        let copy = original.respanned(5..6);
+        assert_eq!(original, Spanned::new("x", 1..2));
+        assert_eq!(copy, Spanned::new("x", 5..6));
+    }
+
+    #[test]
+    fn located_shows_the_byte_span() {
+        let spanned = Spanned::new("ident", 4..9);
+        assert_eq!(spanned.located().to_string(), "ident (4..9)");
+    }
+
+    #[test]
+    fn synthetic_values_are_detectable() {
+        let node = Spanned::synthetic("generated");
+        assert_eq!(node.span(), 0..0);
+        assert!(node.is_synthetic());
+        assert!(!Spanned::new("real", 0..4).is_synthetic());
+        assert!(!Spanned::new("empty", 3..3).is_synthetic());
+    }
+
+    #[test]
+    fn checked_shift_moves_the_span() {
+        assert_eq!(
+            Spanned::new((), 4..6).checked_shift(-4).unwrap().span(),
+            0..2
+        );
+        assert_eq!(
+            Spanned::new((), 4..6).checked_shift(3).unwrap().span(),
+            7..9
+        );
+    }
+
+    #[test]
+    fn checked_shift_returns_none_near_usize_max() {
+        let spanned = Spanned::new((), usize::MAX - 2..usize::MAX - 1);
+        assert!(spanned.clone().checked_shift(1).is_some());
+        assert!(spanned.clone().checked_shift(2).is_none());
+        assert!(spanned.checked_shift(isize::MAX).is_none());
+    }
+
+    #[test]
+    fn checked_shift_returns_none_below_zero() {
+        assert!(Spanned::new((), 1..3).checked_shift(-2).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "span shift overflowed or underflowed")]
+    fn shift_panics_on_overflow() {
+        Spanned::new((), usize::MAX - 1..usize::MAX).shift(1);
+    }
+
+    #[test]
+    fn truncate_saturates_near_usize_max() {
+        let spanned = Spanned::new((), usize::MAX - 1..usize::MAX).truncate(10);
+        assert_eq!(spanned.span(), usize::MAX - 1..usize::MAX);
+    }
+
+    #[test]
+    fn apply_edit_fails_rather_than_overflowing() {
+        let mut spanned = Spanned::new((), usize::MAX - 1..usize::MAX);
+        assert!(!spanned.apply_edit(0, 0, 1));
+        assert_eq!(spanned.span(), usize::MAX - 1..usize::MAX);
+    }
+
+    #[test]
+    fn map_err_span_embeds_the_span_in_the_error() {
+        #[derive(Debug, PartialEq)]
+        struct ParseError {
+            message: &'static str,
+            span: Span,
+        }
+
+        let result: Spanned<Result<(), ParseError>> = Spanned::new(
+            Err(ParseError {
+                message: "unexpected token",
+                span: 0..0,
+            }),
+            5..9,
+        );
+        let result = result.map_err_span(|e, span| ParseError { span, ..e });
+        assert_eq!(
+            result,
+            Spanned::new(
+                Err(ParseError {
+                    message: "unexpected token",
+                    span: 5..9,
+                }),
+                5..9
+            )
+        );
+    }
+
+    #[test]
+    fn validate_accepts_a_valid_span() {
+        assert_eq!(Spanned::new((), 0..1).validate("héllo"), Ok(()));
+        assert_eq!(Spanned::new((), 1..3).validate("héllo"), Ok(()));
+        assert_eq!(Spanned::new((), 6..6).validate("héllo"), Ok(()));
+    }
+
+    #[test]
+    fn validate_rejects_an_out_of_range_span() {
+        assert_eq!(
+            Spanned::new((), 4..7).validate("héllo"),
+            Err(SpanError::OutOfBounds { span: 4..7, len: 6 })
+        );
+    }
+
+    #[test]
+    fn validate_rejects_a_span_inside_a_code_point() {
+        assert_eq!(
+            Spanned::new((), 1..3).validate("éa"),
+            Err(SpanError::NotCharBoundary {
+                span: 1..3,
+                index: 1
+            })
+        );
+        assert_eq!(
+            Spanned::new((), 0..1).validate("éa"),
+            Err(SpanError::NotCharBoundary {
+                span: 0..1,
+                index: 1
+            })
+        );
+    }
+
+    #[test]
+    fn validate_rejects_an_inverted_span() {
+        #[allow(clippy::reversed_empty_ranges)]
+        let span = 2..1;
+        assert_eq!(
+            Spanned::new((), span.clone()).validate("abc"),
+            Err(SpanError::Inverted(span))
+        );
+    }
+
+    #[test]
+    fn converts_into_a_range_for_slicing() {
+        let bytes = Vec::from([10, 20, 30, 40, 50]);
+        let spanned = Spanned::new((), 1..3);
+        assert_eq!(bytes[Range::from(&spanned)], [20, 30]);
+        assert_eq!(spanned.range(), spanned.span());
+    }
+
+    #[test]
+    fn apply_edit_leaves_a_span_before_the_edit_untouched() {
+        let mut spanned = Spanned::new((), 0..3);
+        assert!(spanned.apply_edit(3, 2, 5));
+        assert_eq!(spanned.span(), 0..3);
+    }
+
+    #[test]
+    fn apply_edit_invalidates_a_span_overlapping_the_edit() {
+        let mut spanned = Spanned::new((), 2..6);
+        assert!(!spanned.apply_edit(4, 4, 1));
+        assert_eq!(spanned.span(), 2..6);
+    }
+
+    #[test]
+    fn apply_edit_moves_a_span_after_the_edit() {
+        let mut spanned = Spanned::new((), 8..10);
+        assert!(spanned.apply_edit(2, 4, 1));
+        assert_eq!(spanned.span(), 5..7);
+        assert!(spanned.apply_edit(0, 0, 3));
+        assert_eq!(spanned.span(), 8..10);
+    }
+
+    #[test]
+    fn truncate_shortens_a_long_span() {
+        assert_eq!(
+            Spanned::new('s', 4..100).truncate(10),
+            Spanned::new('s', 4..14)
+        );
+        assert_eq!(
+            Spanned::new('s', 4..8).truncate(10),
+            Spanned::new('s', 4..8)
+        );
+    }
+
+    #[test]
+    fn extend_to_cover_grows_across_children() {
+        let mut node = Spanned::new("block", 10..11);
+        for child in [Spanned::new((), 12..15), Spanned::new((), 16..20)] {
+            node.extend_to_cover(&child);
+        }
+        node.extend_to_cover(&(4..6));
+        assert_eq!(node.span(), 4..20);
+    }
+
+    #[test]
+    fn line_spans_splits_a_span_over_three_lines() {
+        let source = "ab\ncde\nfg";
+        assert_eq!(
+            Spanned::new((), 1..9).line_spans(source),
+            [1..2, 3..6, 7..9]
+        );
+    }
+
+    #[test]
+    #[allow(clippy::single_range_in_vec_init)]
+    fn line_spans_of_a_single_line_span_is_that_span() {
+        let source = "ab\ncde\nfg";
+        assert_eq!(Spanned::new((), 4..6).line_spans(source), [4..6]);
+    }
+
+    #[test]
+    #[allow(clippy::single_range_in_vec_init)]
+    fn line_spans_ignores_the_line_after_a_final_newline() {
+        let source = "ab\ncde\nfg";
+        assert_eq!(Spanned::new((), 3..7).line_spans(source), [3..6]);
+        assert_eq!(Spanned::new((), 3..6).line_spans(source), [3..6]);
+        assert_eq!(Spanned::new((), 0..7).line_spans(source), [0..2, 3..6]);
+    }
+
+    #[test]
+    fn map_both_applies_the_matching_closure_and_keeps_the_span() {
+        let ok: Spanned<Result<i32, &str>> = Spanned::new(Ok(2), 1..4);
+        assert_eq!(
+            ok.map_both(|t| t * 10, |e| e.len()),
+            Spanned::new(Ok(20), 1..4)
+        );
+        let err: Spanned<Result<i32, &str>> = Spanned::new(Err("bad"), 1..4);
+        assert_eq!(
+            err.map_both(|t| t * 10, |e| e.len()),
+            Spanned::new(Err(3), 1..4)
+        );
+    }
+
+    #[test]
+    #[allow(clippy::reversed_empty_ranges)]
+    fn normalize_reorders_an_inverted_span() {
+        let spanned = Spanned::new((), 7..3);
+        assert!(spanned.is_inverted());
+        let spanned = spanned.normalize();
+        assert!(!spanned.is_inverted());
+        assert_eq!(spanned.span(), 3..7);
+    }
+
+    #[test]
+    fn normalize_never_clamps() {
+        let spanned = Spanned::new((), 3..usize::MAX).normalize();
+        assert_eq!(spanned.span(), 3..usize::MAX);
+    }
+
+    #[test]
+    fn chains_a_single_value_onto_a_stream() {
+        let tokens = vec![Spanned::new('a', 0..1), Spanned::new('b', 1..2)];
+        let eof = Spanned::new('$', 2..2);
+        let chained: Vec<Spanned<char>> = tokens.into_iter().chain(eof).collect();
+        assert_eq!(chained.len(), 3);
+        assert_eq!(chained[2], Spanned::new('$', 2..2));
+
+        let once: Vec<Spanned<char>> = Spanned::new('x', 0..1).once().collect();
+        assert_eq!(once, [Spanned::new('x', 0..1)]);
+    }
+
+    #[test]
+    fn iterates_over_itself_rather_than_its_value() {
+        let list = Spanned::new(vec![1, 2, 3], 0..5);
+        let items: Vec<Spanned<Vec<i32>>> = list.into_iter().collect();
+        assert_eq!(items, [Spanned::new(vec![1, 2, 3], 0..5)]);
+    }
+
+    #[test]
+    fn map_ref_derives_a_value_without_consuming() {
+        let name = Spanned::new(String::from("ident"), 2..7);
+        let len: Spanned<usize> = name.map_ref(String::len);
+        assert_eq!(len, Spanned::new(5, 2..7));
+        assert_eq!(name.inner, "ident");
+    }
+
+    #[test]
+    fn stable_key_is_identical_across_constructions() {
+        let first = Spanned::new(String::from("ident"), 3..8);
+        let second = Spanned::new(String::from("ident"), 3..8);
+        assert_eq!(first.stable_key(), second.stable_key());
+        assert_ne!(
+            first.stable_key(),
+            Spanned::new(String::from("ident"), 4..9).stable_key()
+        );
+        assert_ne!(
+            first.stable_key(),
+            Spanned::new(String::from("other"), 3..8).stable_key()
+        );
+    }
+
+    #[test]
+    fn stable_key_does_not_depend_on_the_platform() {
+        // FNV-1a over `7u32`, `1u64` and `2u64` as little-endian bytes.
+        assert_eq!(
+            Spanned::new(7u32, 1..2).stable_key(),
+            6_415_464_585_265_619_649
+        );
+    }
+
+    #[test]
+    fn borrow_span_from_adopts_the_span_of_another_value() {
+        let token = Spanned::new("42", 6..8);
+        let value = Spanned::borrow_span_from(42, &token);
+        assert_eq!(value, Spanned::new(42, 6..8));
+    }
+
+    #[test]
+    fn map_opt_keeps_the_span_of_some_and_none() {
+        let some = Spanned::new(Some(2), 3..4);
+        assert_eq!(some.map_opt(|n| n * 2), Spanned::new(Some(4), 3..4));
+        let none: Spanned<Option<i32>> = Spanned::new(None, 3..4);
+        assert_eq!(none.map_opt(|n| n * 2), Spanned::new(None, 3..4));
+    }
+
+    #[test]
+    fn map_with_span_passes_the_span_to_the_closure() {
+        let spanned = Spanned::new("x", 7..8);
+        let mapped = spanned.map_with_span(|name, span| (name, span.start));
+        assert_eq!(mapped, Spanned::new(("x", 7), 7..8));
+    }
+
+    #[test]
+    fn or_default_substitutes_the_default_for_an_error() {
+        let ok: Spanned<Result<i32, &str>> = Spanned::new(Ok(5), 1..2);
+        assert_eq!(ok.or_default(), Spanned::new(5, 1..2));
+        let err: Spanned<Result<i32, &str>> = Spanned::new(Err("bad"), 1..2);
+        assert_eq!(err.or_default(), Spanned::new(0, 1..2));
+    }
+
+    #[test]
+    fn display_to_spanned_renders_the_value() {
+        let number = Spanned::new(-42, 3..6);
+        assert_eq!(
+            number.display_to_spanned(),
+            Spanned::new(String::from("-42"), 3..6)
+        );
+    }
+
+    #[test]
+    fn overlapping_spans_overlap_and_touch() {
+        let spanned = Spanned::new((), 1..5);
+        assert!(spanned.overlaps(&(4..8)));
+        assert!(spanned.overlaps(&(2..3)));
+        assert!(spanned.touches(&(4..8)));
+    }
+
+    #[test]
+    fn adjacent_spans_touch_but_do_not_overlap() {
+        let spanned = Spanned::new((), 1..3);
+        assert!(!spanned.overlaps(&(3..5)));
+        assert!(spanned.touches(&(3..5)));
+        assert!(!Spanned::new((), 3..5).overlaps(&(1..3)));
+        assert!(Spanned::new((), 3..5).touches(&(1..3)));
+    }
+
+    #[test]
+    fn gapped_spans_neither_overlap_nor_touch() {
+        let spanned = Spanned::new((), 1..3);
+        assert!(!spanned.overlaps(&(4..6)));
+        assert!(!spanned.touches(&(4..6)));
+    }
+
+    #[test]
+    fn empty_spans_touch_but_never_overlap() {
+        let empty = Spanned::new((), 3..3);
+        for other in [1..5, 1..3, 3..5, 3..3] {
+            assert!(!empty.overlaps(&other), "{:?}", other);
+            assert!(empty.touches(&other), "{:?}", other);
+        }
+        assert!(!empty.touches(&(4..6)));
+        assert!(!Spanned::new((), 1..5).overlaps(&(3..3)));
+    }
+
+    #[test]
+    fn retag_reclassifies_a_token() {
+        #[derive(Debug, PartialEq)]
+        enum Keyword {
+            Type,
+        }
+        #[derive(Debug, PartialEq)]
+        struct Ident(&'static str);
+
+        let keyword = Spanned::new(Keyword::Type, 4..8);
+        assert_eq!(keyword.inner, Keyword::Type);
+        let ident: Spanned<Ident> = keyword.retag(Ident("type"));
+        assert_eq!(ident, Spanned::new(Ident("type"), 4..8));
+    }
+
+    #[test]
+    fn project_borrows_a_field_with_the_parent_span() {
+        struct Call {
+            name: String,
+            args: Vec<i32>,
+        }
+
+        let call = Spanned::new(
+            Call {
+                name: String::from("f"),
+                args: vec![1, 2],
+            },
+            0..7,
+        );
+        let name: Spanned<&String> = call.project(|call| &call.name);
+        assert_eq!(name, Spanned::new(&String::from("f"), 0..7));
+        assert_eq!(call.project(|call| &call.args).len(), 2);
+    }
+
+    #[test]
+    fn map_each_maps_every_element_and_keeps_the_span() {
+        let list = Spanned::new(vec![1, 2, 3], 0..9);
+        let strings: Spanned<Vec<String>> = list.map_each(|n| n.to_string());
+        assert_eq!(
+            strings,
+            Spanned::new(
+                vec![String::from("1"), String::from("2"), String::from("3")],
+                0..9
+            )
+        );
+    }
+
+    #[test]
+    fn filter_each_keeps_matching_elements_and_the_span() {
+        let list = Spanned::new(vec![1, 2, 3, 4], 0..9);
+        assert_eq!(
+            list.filter_each(|n| n % 2 == 0),
+            Spanned::new(vec![2, 4], 0..9)
+        );
+    }
+
+    #[test]
+    fn anchored_moves_a_relative_span_to_its_origin() {
+        assert_eq!(Spanned::new('x', 2..5).anchored(100).span(), 102..105);
+    }
+
+    #[test]
+    #[should_panic(expected = "anchored span overflowed")]
+    fn anchored_panics_on_overflow() {
+        Spanned::new('x', 2..5).anchored(usize::MAX - 3);
+    }
+
+    #[test]
+    fn widen_to_lines_covers_whole_lines() {
+        let source = "fn main() {\n    let x = 1;\n}\n";
+        assert_eq!(Spanned::new((), 20..21).widen_to_lines(source), 12..26);
+        assert_eq!(Spanned::new((), 4..18).widen_to_lines(source), 0..26);
+        assert_eq!(Spanned::new((), 12..27).widen_to_lines(source), 12..26);
+        assert_eq!(Spanned::new((), 28..28).widen_to_lines(source), 27..28);
+        assert_eq!(Spanned::new((), 29..29).widen_to_lines(source), 29..29);
+    }
+
+    #[test]
+    fn narrow_to_trimmed_excludes_surrounding_whitespace() {
+        let source = "(  a b \n)";
+        assert_eq!(Spanned::new((), 1..8).narrow_to_trimmed(source), 3..6);
+        assert_eq!(Spanned::new((), 3..6).narrow_to_trimmed(source), 3..6);
+        assert_eq!(Spanned::new((), 6..8).narrow_to_trimmed(source), 8..8);
+    }
+
+    #[test]
+    fn narrow_to_trimmed_stays_on_char_boundaries() {
+        let source = "\u{3000}名\u{3000}";
+        let span = Spanned::new((), 0..9).narrow_to_trimmed(source);
+        assert_eq!(span, 3..6);
+        assert_eq!(&source[span], "名");
+    }
+
+    #[test]
+    fn as_tuple_borrows_both_parts() {
+        let spanned = Spanned::new(String::from("x"), 3..4);
+        let (inner, span) = spanned.as_tuple();
+        assert!(std::ptr::eq(inner, &spanned.inner));
+        assert_eq!((inner.as_str(), span), ("x", &(3..4)));
+    }
+
+    #[test]
+    fn cmp_by_len_breaks_ties_by_start() {
+        let short = Spanned::new((), 5..6);
+        let long = Spanned::new((), 0..4);
+        assert_eq!(short.cmp_by_len(&long), Ordering::Less);
+        assert_eq!(
+            Spanned::new((), 0..2).cmp_by_len(&Spanned::new((), 3..5)),
+            Ordering::Less
+        );
+        assert_eq!(
+            Spanned::new((), 3..5).cmp_by_len(&Spanned::new((), 3..5)),
+            Ordering::Equal
+        );
+    }
+
+    #[test]
+    fn first_and_second_borrow_the_parts_of_a_pair() {
+        let token = Spanned::new(("ident", String::from("x")), 4..5);
+        assert_eq!(*token.first(), "ident");
+        assert_eq!(token.second(), "x");
+        assert_eq!(token.span(), 4..5);
+    }
+
+    #[test]
+    fn subspan_carves_a_child_out_of_the_span() {
+        let parent = Spanned::new((), 10..20);
+        assert_eq!(parent.subspan(&(2..5)), Some(12..15));
+        assert_eq!(parent.subspan(&(0..10)), Some(10..20));
+        assert_eq!(parent.subspan(&(10..10)), Some(20..20));
+    }
+
+    #[test]
+    fn subspan_rejects_a_range_outside_the_span() {
+        let parent = Spanned::new((), 10..20);
+        assert_eq!(parent.subspan(&(5..11)), None);
+        assert_eq!(parent.subspan(&(usize::MAX..usize::MAX)), None);
+        #[allow(clippy::reversed_empty_ranges)]
+        let inverted = 5..2;
+        assert_eq!(parent.subspan(&inverted), None);
+    }
+}
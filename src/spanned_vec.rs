@@ -0,0 +1,182 @@
+use std::{ops::Index, slice::Iter};
+
+use crate::{span_union, HasSpan, Span, Spanned};
+
+/// A list of [`struct@Spanned`] values that also tracks the span covering all of them.
+///
+/// Unlike `Spanned<Vec<T>>`, every element keeps its own span.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpannedVec<T> {
+    items: Vec<Spanned<T>>,
+    span: Span,
+}
+
+impl<T> SpannedVec<T> {
+    /// Creates a new, empty `SpannedVec`.
+    pub fn new() -> Self {
+        Self {
+            items: Vec::new(),
+            span: 0..0,
+        }
+    }
+
+    /// Creates a new, empty `SpannedVec` with space for at least `capacity` elements.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            items: Vec::with_capacity(capacity),
+            span: 0..0,
+        }
+    }
+
+    /// Reserves space for at least `additional` more elements, see [`Vec::reserve`].
+    pub fn reserve(&mut self, additional: usize) {
+        self.items.reserve(additional);
+    }
+
+    /// The number of elements there is space for without reallocating.
+    pub fn capacity(&self) -> usize {
+        self.items.capacity()
+    }
+
+    /// Shrinks the capacity as much as possible, see [`Vec::shrink_to_fit`].
+    pub fn shrink_to_fit(&mut self) {
+        self.items.shrink_to_fit();
+    }
+
+    /// Appends an element, growing the covering span to include it.
+    pub fn push(&mut self, item: Spanned<T>) {
+        let span = item.span();
+        if self.items.is_empty() {
+            self.span = span;
+        } else {
+            self.span = span_union(&[self.span.clone(), span]);
+        }
+        self.items.push(item);
+    }
+
+    /// An iterator over the elements.
+    pub fn iter(&self) -> Iter<'_, Spanned<T>> {
+        self.items.iter()
+    }
+
+    /// The span covering every element, or `0..0` if there are none.
+    pub fn span(&self) -> Span {
+        self.span.clone()
+    }
+
+    /// The number of elements.
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Returns `true` if there are no elements.
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Consume `self` and return the inner list of elements.
+    pub fn into_inner(self) -> Vec<Spanned<T>> {
+        self.items
+    }
+}
+
+impl<T> Default for SpannedVec<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Extend<Spanned<T>> for SpannedVec<T> {
+    /// Appends every element of `iter`, growing the covering span to include them.
+    fn extend<I: IntoIterator<Item = Spanned<T>>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        for item in iter {
+            self.push(item);
+        }
+    }
+}
+
+impl<T> HasSpan for SpannedVec<T> {
+    fn span(&self) -> Span {
+        self.span.clone()
+    }
+}
+
+impl<T> Index<usize> for SpannedVec<T> {
+    type Output = Spanned<T>;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.items[index]
+    }
+}
+
+impl<'a, T> IntoIterator for &'a SpannedVec<T> {
+    type Item = &'a Spanned<T>;
+    type IntoIter = Iter<'a, Spanned<T>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T> IntoIterator for SpannedVec<T> {
+    type Item = Spanned<T>;
+    type IntoIter = std::vec::IntoIter<Spanned<T>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn push_grows_the_covering_span() {
+        let mut list = SpannedVec::new();
+        assert_eq!(list.span(), 0..0);
+        list.push(Spanned::new('a', 4..5));
+        assert_eq!(list.span(), 4..5);
+        list.push(Spanned::new('b', 7..9));
+        list.push(Spanned::new('c', 2..3));
+        assert_eq!(list.span(), 2..9);
+        assert_eq!(list.len(), 3);
+    }
+
+    #[test]
+    fn elements_keep_their_own_spans() {
+        let mut list = SpannedVec::new();
+        list.push(Spanned::new('a', 0..1));
+        list.push(Spanned::new('b', 2..3));
+        assert_eq!(list[1], Spanned::new('b', 2..3));
+        let spans: Vec<Span> = list.iter().map(Spanned::span).collect();
+        assert_eq!(spans, [0..1, 2..3]);
+    }
+
+    #[test]
+    fn with_capacity_preallocates() {
+        let mut list: SpannedVec<char> = SpannedVec::with_capacity(16);
+        assert!(list.capacity() >= 16);
+        assert!(list.is_empty());
+        list.reserve(32);
+        assert!(list.capacity() >= 32);
+        list.push(Spanned::new('a', 0..1));
+        list.shrink_to_fit();
+        assert!(list.capacity() >= 1);
+    }
+
+    #[test]
+    fn extend_grows_the_covering_span() {
+        let mut list = SpannedVec::new();
+        list.push(Spanned::new('a', 5..6));
+        list.extend([Spanned::new('b', 7..8), Spanned::new('c', 1..2)]);
+        assert_eq!(list.len(), 3);
+        assert_eq!(list.span(), 1..8);
+
+        let mut empty = SpannedVec::new();
+        empty.extend([Spanned::new('a', 5..6)]);
+        assert_eq!(empty.span(), 5..6);
+    }
+}
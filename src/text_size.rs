@@ -0,0 +1,103 @@
+use std::{error::Error, fmt::Display};
+
+use ::text_size::{TextRange, TextSize};
+
+use crate::{Sliced, Span, Spanned};
+
+/// The reason a span could not be converted into a [`TextRange`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TextRangeError {
+    /// The span's start is greater than its end.
+    Inverted(Span),
+    /// An end of the span does not fit in a `u32`.
+    Overflow(Span),
+}
+
+impl Display for TextRangeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TextRangeError::Inverted(span) => write!(f, "span {:?} is inverted", span),
+            TextRangeError::Overflow(span) => {
+                write!(f, "span {:?} does not fit in a text range", span)
+            }
+        }
+    }
+}
+
+impl Error for TextRangeError {}
+
+fn to_text_range(span: Span) -> Result<TextRange, TextRangeError> {
+    if span.start > span.end {
+        return Err(TextRangeError::Inverted(span));
+    }
+    match (u32::try_from(span.start), u32::try_from(span.end)) {
+        (Ok(start), Ok(end)) => Ok(TextRange::new(TextSize::new(start), TextSize::new(end))),
+        _ => Err(TextRangeError::Overflow(span)),
+    }
+}
+
+impl<T> Spanned<T> {
+    /// Creates a new `Spanned` value from a [`TextRange`].
+    ///
+    /// # Parameters
+    /// - `inner`: The value to be wrapped.
+    /// - `range`: The range in the source text that corresponds to the value.
+    pub fn from_text_range(inner: T, range: TextRange) -> Self {
+        Self::new(inner, range.start().into()..range.end().into())
+    }
+}
+
+/// Fails if the span is inverted or either end of it does not fit in a `u32`.
+impl<T> TryFrom<&Spanned<T>> for TextRange {
+    type Error = TextRangeError;
+
+    fn try_from(value: &Spanned<T>) -> Result<Self, Self::Error> {
+        to_text_range(value.span())
+    }
+}
+
+/// Fails if the span is inverted or either end of it does not fit in a `u32`.
+impl<T> TryFrom<&Sliced<'_, T>> for TextRange {
+    type Error = TextRangeError;
+
+    fn try_from(value: &Sliced<'_, T>) -> Result<Self, Self::Error> {
+        to_text_range(value.span())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_to_and_from_text_range() {
+        let range = TextRange::try_from(&Spanned::new((), 2..5)).unwrap();
+        assert_eq!(range, TextRange::new(2.into(), 5.into()));
+        assert_eq!(Spanned::from_text_range((), range).span(), 2..5);
+
+        let sliced = Sliced::new((), 1..3, "abcd");
+        assert_eq!(
+            TextRange::try_from(&sliced),
+            Ok(TextRange::new(1.into(), 3.into()))
+        );
+    }
+
+    #[test]
+    fn fails_if_an_end_overflows_u32() {
+        let span = 0..u32::MAX as usize + 1;
+        assert_eq!(
+            TextRange::try_from(&Spanned::new((), span.clone())),
+            Err(TextRangeError::Overflow(span))
+        );
+    }
+
+    #[test]
+    fn fails_rather_than_panicking_on_an_inverted_span() {
+        #[allow(clippy::reversed_empty_ranges)]
+        let span = 5..2;
+        assert_eq!(
+            TextRange::try_from(&Spanned::new((), span.clone())),
+            Err(TextRangeError::Inverted(span))
+        );
+    }
+}
@@ -0,0 +1,97 @@
+use crate::{HasSpan, Sliced, Span, Spanned};
+
+/// A piece of a lossless token stream produced by [`with_trivia`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Piece<'source, T> {
+    /// Source text between tokens, such as whitespace and comments.
+    Trivia(Sliced<'source, &'source str>),
+    /// A token from the original list.
+    Token(Spanned<T>),
+}
+
+impl<T> HasSpan for Piece<'_, T> {
+    fn span(&self) -> Span {
+        match self {
+            Piece::Trivia(trivia) => trivia.span(),
+            Piece::Token(token) => token.span(),
+        }
+    }
+}
+
+/// Interleaves `items` with the text of `source` in the gaps around them, so that every byte of
+/// `source` is covered.
+///
+/// `items` must be sorted by position. Each non-empty gap, including any before the first item
+/// and after the last, becomes a [`Piece::Trivia`]. Concatenating the slice of each piece
+/// reproduces `source` exactly, provided the items don't overlap.
+pub fn with_trivia<'source, T>(
+    items: Vec<Spanned<T>>,
+    source: &'source str,
+) -> Vec<Piece<'source, T>> {
+    let mut pieces = Vec::with_capacity(items.len() * 2 + 1);
+    let mut end = 0;
+    let push_gap = |pieces: &mut Vec<Piece<'source, T>>, gap: Span| {
+        if let Some(text) = source.get(gap.clone()).filter(|text| !text.is_empty()) {
+            pieces.push(Piece::Trivia(Sliced::new(text, gap, source)));
+        }
+    };
+    for item in items {
+        let span = item.span();
+        push_gap(&mut pieces, end..span.start);
+        end = end.max(span.end);
+        pieces.push(Piece::Token(item));
+    }
+    push_gap(&mut pieces, end..source.len());
+    pieces
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn concatenating_the_pieces_reproduces_the_source() {
+        let source = "  let x = 1; // done\n";
+        let tokens = vec![
+            Spanned::new("let", 2..5),
+            Spanned::new("x", 6..7),
+            Spanned::new("=", 8..9),
+            Spanned::new("1", 10..11),
+            Spanned::new(";", 11..12),
+        ];
+        let pieces = with_trivia(tokens, source);
+        let rebuilt: String = pieces
+            .iter()
+            .map(|piece| match piece {
+                Piece::Trivia(trivia) => trivia.slice(),
+                Piece::Token(token) => &source[token.span()],
+            })
+            .collect();
+        assert_eq!(rebuilt, source);
+
+        let spans: Vec<Span> = pieces.iter().map(HasSpan::span).collect();
+        assert_eq!(
+            spans,
+            [
+                0..2,
+                2..5,
+                5..6,
+                6..7,
+                7..8,
+                8..9,
+                9..10,
+                10..11,
+                11..12,
+                12..21
+            ]
+        );
+    }
+
+    #[test]
+    fn no_tokens_is_a_single_piece_of_trivia() {
+        let source = " \n";
+        let pieces = with_trivia::<()>(Vec::new(), source);
+        assert_eq!(pieces, [Piece::Trivia(Sliced::new(source, 0..2, source))]);
+        assert!(with_trivia::<()>(Vec::new(), "").is_empty());
+    }
+}
@@ -0,0 +1,144 @@
+use std::cmp::Reverse;
+
+use ::unicode_width::UnicodeWidthStr;
+
+use crate::{span::overlaps, Sliced, Span};
+
+impl<T> Sliced<'_, T> {
+    /// The displayed width of the slice in columns, as used for aligning text in a terminal.
+    ///
+    /// Unlike [`Sliced::char_len`] this accounts for wide characters, such as CJK characters,
+    /// and zero width characters, such as combining marks.
+    pub fn width(&self) -> usize {
+        self.slice().width()
+    }
+}
+
+/// Renders `line` with each annotation's span underlined by carets and labelled, aligned by
+/// displayed width so that wide characters, such as CJK characters, don't misalign them.
+///
+/// ```text
+/// let 名前 = value;
+///     ^^^^   ^^^^^ value
+///     name
+/// ```
+///
+/// The label of an annotation follows its carets where there's room, otherwise it's stacked on
+/// the first row below the carets where it doesn't collide with another label. An empty span is
+/// marked by a single caret, and empty labels are omitted.
+///
+/// # Panics
+/// Panics if any span is not valid for `line`.
+pub fn align_annotations(line: &str, annotations: &[(Span, &str)]) -> String {
+    let mut marks: Vec<(usize, usize, &str)> = annotations
+        .iter()
+        .map(|(span, label)| {
+            let width = line[span.clone()].width().max(1);
+            (line[..span.start].width(), width, *label)
+        })
+        .collect();
+
+    // Row 0 holds the carets, and the labels which fit after them.
+    let mut rows: Vec<Vec<(usize, String)>> = vec![caret_runs(&marks)];
+    let mut occupied: Vec<Vec<Span>> =
+        vec![marks.iter().map(|&(col, w, _)| col..col + w).collect()];
+    let is_free = |taken: &[Span], wanted: &Span| !taken.iter().any(|span| overlaps(span, wanted));
+
+    // Place the rightmost labels first, so they get the room after their carets.
+    marks.sort_by_key(|&(col, _, _)| Reverse(col));
+    for (col, width, label) in marks.into_iter().filter(|(_, _, label)| !label.is_empty()) {
+        let label_width = label.width();
+        let after = col + width..col + width + 1 + label_width;
+        if is_free(&occupied[0], &after) {
+            occupied[0].push(after);
+            rows[0].push((col + width + 1, label.to_string()));
+            continue;
+        }
+        let wanted = col..col + label_width + 1;
+        let row = (1..)
+            .find(|&row| {
+                occupied
+                    .get(row)
+                    .is_none_or(|taken| is_free(taken, &wanted))
+            })
+            .expect("a free row always exists");
+        if row == rows.len() {
+            rows.push(Vec::new());
+            occupied.push(Vec::new());
+        }
+        occupied[row].push(wanted);
+        rows[row].push((col, label.to_string()));
+    }
+
+    let mut out = line.to_string();
+    if annotations.is_empty() {
+        return out;
+    }
+    for mut row in rows {
+        row.sort_by_key(|&(col, _)| col);
+        out.push('\n');
+        let mut cursor = 0;
+        for (col, text) in row {
+            out.push_str(&" ".repeat(col.saturating_sub(cursor)));
+            cursor = col + text.width();
+            out.push_str(&text);
+        }
+    }
+    out
+}
+
+/// The runs of carets underlining every mark, as `(column, carets)` pairs.
+fn caret_runs(marks: &[(usize, usize, &str)]) -> Vec<(usize, String)> {
+    let end = marks.iter().map(|&(col, w, _)| col + w).max().unwrap_or(0);
+    let mut underlined = vec![false; end];
+    for &(col, width, _) in marks {
+        underlined[col..col + width].fill(true);
+    }
+    let mut runs = Vec::new();
+    let mut col = 0;
+    while col < end {
+        let len = underlined[col..]
+            .iter()
+            .take_while(|&&u| u == underlined[col])
+            .count();
+        if underlined[col] {
+            runs.push((col, "^".repeat(len)));
+        }
+        col += len;
+    }
+    runs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn width_counts_displayed_columns() {
+        let source = "a名e\u{301}";
+        let sliced = Sliced::new((), 0..source.len(), source);
+        assert_eq!(sliced.span().len(), 7);
+        assert_eq!(sliced.char_len(), 4);
+        assert_eq!(sliced.width(), 4);
+        assert_eq!(Sliced::new((), 1..4, source).width(), 2);
+    }
+
+    #[test]
+    fn align_annotations_aligns_carets_after_a_wide_character() {
+        let line = "let 名 = x;";
+        assert_eq!(
+            align_annotations(line, &[(4..7, "name"), (10..11, "value")]),
+            concat!("let 名 = x;\n", "    ^^   ^ value\n", "    name")
+        );
+    }
+
+    #[test]
+    fn align_annotations_stacks_overlapping_labels() {
+        let line = "名前 = 1";
+        assert_eq!(
+            align_annotations(line, &[(0..3, "first"), (3..6, "second")]),
+            concat!("名前 = 1\n", "^^^^ second\n", "first")
+        );
+        assert_eq!(align_annotations(line, &[]), line);
+    }
+}